@@ -0,0 +1,35 @@
+//! Premultiplied alpha conversion.
+
+use std::cmp;
+
+/// How color channels are stored relative to alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alpha {
+    /// Color channels are stored independently of alpha.
+    Straight,
+    /// Color channels are multiplied by alpha.
+    Premultiplied,
+}
+
+/// Multiplies the color channels of RGBA8 memory by alpha.
+pub fn premultiply(memory: &mut [u8]) {
+    for pixel in memory.chunks_mut(4) {
+        let a = pixel[3] as u16;
+        for c in &mut pixel[..3] {
+            *c = ((*c as u16 * a + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Divides the color channels of premultiplied RGBA8 memory by alpha.
+///
+/// Pixels with zero alpha are left unchanged.
+pub fn unpremultiply(memory: &mut [u8]) {
+    for pixel in memory.chunks_mut(4) {
+        let a = pixel[3] as u16;
+        if a == 0 { continue; }
+        for c in &mut pixel[..3] {
+            *c = cmp::min((*c as u16 * 255 + a / 2) / a, 255) as u8;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! 2D array textures.
+
+use std::path::Path;
+use gfx;
+use image::{self, FilterType, RgbaImage};
+use texture::TextureSettings;
+
+use {dimension, mipmap_chain, texture_size};
+use {Alpha, ColorSpace, Error, Flip, PixelFormat, Texture, Usage};
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a 2D array texture with one layer per image.
+    ///
+    /// All images must have the same size.
+    /// Shaders sample the layers by index.
+    /// Generates mipmaps for each layer with a triangle filter when enabled
+    /// in settings.
+    pub fn from_image_array<F>(
+        factory: &mut F,
+        images: &[RgbaImage],
+        flip: Flip,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        Texture::from_image_array_with_color_space(factory, images, flip,
+            ColorSpace::Srgb, settings)
+    }
+
+    /// Creates a 2D array texture with one layer per image
+    /// in the given color space.
+    pub fn from_image_array_with_color_space<F>(
+        factory: &mut F,
+        images: &[RgbaImage],
+        flip: Flip,
+        color_space: ColorSpace,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = match images.first() {
+            Some(img) => img.dimensions(),
+            None => return Err(Error::ZeroSize),
+        };
+        let layers = try!(dimension(images.len() as u32));
+
+        // One slice per level of each layer.
+        let mut slices = vec![];
+        for img in images {
+            let (w, h) = img.dimensions();
+            if [w, h] != [width, height] {
+                return Err(Error::SizeMismatch {
+                    expected: [width, height],
+                    found: [w, h],
+                });
+            }
+            let img = flip.apply(img);
+            let chain = if settings.get_generate_mipmap() {
+                mipmap_chain(&img, FilterType::Triangle)
+            } else {
+                vec![]
+            };
+            slices.push(img);
+            slices.extend(chain);
+        }
+        let num_levels = slices.len() / images.len();
+
+        let size = flip.size([width, height]);
+        let (width, height) = try!(texture_size(size));
+        let kind = gfx::tex::Kind::D2Array(width, height, layers,
+                                           gfx::tex::AaMode::Single);
+        let format = PixelFormat::Rgba8.with_color_space(color_space);
+        let data: Vec<&[u8]> = slices.iter().map(|s| &**s).collect();
+        Texture::create_surface(factory, kind, format, &data,
+                                num_levels as gfx::tex::Level,
+                                Alpha::Straight, Usage::Immutable, settings)
+    }
+
+    /// Creates a 2D array texture with one layer per image file.
+    pub fn from_path_array<F, P>(
+        factory: &mut F,
+        paths: &[P],
+        flip: Flip,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>,
+              P: AsRef<Path>
+    {
+        let mut images = Vec::with_capacity(paths.len());
+        for path in paths {
+            images.push(try!(image::open(path)).to_rgba());
+        }
+        Texture::from_image_array(factory, &images, flip, settings)
+    }
+}
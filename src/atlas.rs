@@ -0,0 +1,236 @@
+//! Packing of many images into shared textures.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::Path;
+use gfx;
+use image::{self, RgbaImage};
+use texture::{ImageSize, TextureSettings};
+
+use packer::Packer;
+use {Error, Flip, Texture};
+
+/// Location of a packed image within an atlas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    /// Index of the texture holding the image.
+    pub texture: usize,
+    /// Pixel rectangle `[x, y, w, h]` within the texture.
+    pub rect: [u32; 4],
+    /// Texture coordinates `[u0, v0, u1, v1]` of the rectangle.
+    pub uv: [f32; 4],
+}
+
+impl Region {
+    /// Creates a region from a pixel rectangle within a texture.
+    pub fn new(texture: usize, rect: [u32; 4], size: [u32; 2]) -> Region {
+        let (w, h) = (size[0] as f32, size[1] as f32);
+        Region {
+            texture: texture,
+            rect: rect,
+            uv: [
+                rect[0] as f32 / w,
+                rect[1] as f32 / h,
+                (rect[0] + rect[2]) as f32 / w,
+                (rect[1] + rect[3]) as f32 / h,
+            ],
+        }
+    }
+}
+
+impl ImageSize for Region {
+    #[inline(always)]
+    fn get_size(&self) -> (u32, u32) {
+        (self.rect[2], self.rect[3])
+    }
+}
+
+/// Collects images and packs them into an atlas.
+pub struct AtlasBuilder<K> {
+    size: [u32; 2],
+    padding: u32,
+    extrude: bool,
+    images: Vec<(K, RgbaImage)>,
+}
+
+impl<K: Hash + Eq> AtlasBuilder<K> {
+    /// Creates a new builder where each texture has the given size.
+    pub fn new(size: [u32; 2]) -> AtlasBuilder<K> {
+        AtlasBuilder {
+            size: size,
+            padding: 1,
+            extrude: true,
+            images: vec![],
+        }
+    }
+
+    /// Sets the number of pixels between packed images.
+    pub fn padding(mut self, val: u32) -> Self {
+        self.padding = val;
+        self
+    }
+
+    /// Sets whether to fill padding with the edge pixels of each image.
+    ///
+    /// This prevents neighbouring images bleeding in when filtering.
+    pub fn extrude(mut self, val: bool) -> Self {
+        self.extrude = val;
+        self
+    }
+
+    /// Adds an image, replacing any image added before with the same key.
+    pub fn add(&mut self, key: K, img: RgbaImage) {
+        if let Some(entry) = self.images.iter_mut().find(|e| e.0 == key) {
+            entry.1 = img;
+            return;
+        }
+        self.images.push((key, img));
+    }
+
+    /// Adds an image loaded from path.
+    pub fn add_path<P>(&mut self, key: K, path: P) -> Result<(), Error>
+        where P: AsRef<Path>
+    {
+        let img = try!(image::open(path));
+        self.add(key, img.to_rgba());
+        Ok(())
+    }
+
+    /// Packs the images and uploads them as textures.
+    pub fn build<F, R>(
+        self,
+        factory: &mut F,
+        settings: &TextureSettings
+    ) -> Result<Atlas<R, K>, Error>
+        where F: gfx::Factory<R>,
+              R: gfx::Resources
+    {
+        let AtlasBuilder { size, padding, extrude, mut images } = self;
+
+        // Packing tall images first leaves a flatter skyline.
+        images.sort_by(|a, b| {
+            let (aw, ah) = a.1.dimensions();
+            let (bw, bh) = b.1.dimensions();
+            bh.cmp(&ah).then(bw.cmp(&aw))
+        });
+
+        let mut pages: Vec<(Packer, RgbaImage)> = vec![];
+        let mut regions = HashMap::new();
+        for (key, img) in images {
+            let (w, h) = img.dimensions();
+            if w == 0 || h == 0 {
+                return Err(Error::ZeroSize);
+            }
+            let padded = [w + 2 * padding, h + 2 * padding];
+            if padded[0] > size[0] || padded[1] > size[1] {
+                return Err(Error::DoesNotFit([w, h]));
+            }
+
+            let mut found = None;
+            for (i, &mut (ref mut packer, _)) in pages.iter_mut().enumerate() {
+                if let Some(pos) = packer.pack(padded) {
+                    found = Some((i, pos));
+                    break;
+                }
+            }
+            let (i, pos) = match found {
+                Some(found) => found,
+                None => {
+                    let mut packer = Packer::new(size);
+                    let pos = try!(packer.pack(padded)
+                        .ok_or(Error::DoesNotFit([w, h])));
+                    pages.push((packer, RgbaImage::new(size[0], size[1])));
+                    (pages.len() - 1, pos)
+                }
+            };
+
+            let (x, y) = (pos[0] + padding, pos[1] + padding);
+            let border = if extrude { padding } else { 0 };
+            blit(&mut pages[i].1, &img, [x, y], border);
+            regions.insert(key, Region::new(i, [x, y, w, h], size));
+        }
+
+        let mut textures = Vec::with_capacity(pages.len());
+        for (_, page) in pages {
+            textures.push(try!(Texture::from_image(factory, &page,
+                                                   Flip::None, settings)));
+        }
+        Ok(Atlas { textures: textures, regions: regions })
+    }
+}
+
+/// Copies an image into a page, repeating edge pixels into the border.
+fn blit(page: &mut RgbaImage, img: &RgbaImage, pos: [u32; 2], border: u32) {
+    let (w, h) = img.dimensions();
+    let (page_w, page_h) = page.dimensions();
+    let x0 = pos[0] - border;
+    let y0 = pos[1] - border;
+    let x1 = cmp::min(pos[0] + w + border, page_w);
+    let y1 = cmp::min(pos[1] + h + border, page_h);
+    for y in y0..y1 {
+        let src_y = cmp::min(y.saturating_sub(pos[1]), h - 1);
+        for x in x0..x1 {
+            let src_x = cmp::min(x.saturating_sub(pos[0]), w - 1);
+            page.put_pixel(x, y, *img.get_pixel(src_x, src_y));
+        }
+    }
+}
+
+/// Images packed into one or more textures.
+pub struct Atlas<R, K> where R: gfx::Resources {
+    /// Textures holding the packed images.
+    pub textures: Vec<Texture<R>>,
+    regions: HashMap<K, Region>,
+}
+
+impl<R: gfx::Resources, K: Hash + Eq> Atlas<R, K> {
+    /// Returns the region of an image.
+    pub fn get(&self, key: &K) -> Option<Region> {
+        self.regions.get(key).cloned()
+    }
+
+    /// Returns the texture holding a region.
+    pub fn texture(&self, region: &Region) -> &Texture<R> {
+        &self.textures[region.texture]
+    }
+
+    /// Returns the number of images in the atlas.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Returns `true` if the atlas contains no images.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use super::{blit, AtlasBuilder};
+
+    #[test]
+    fn add_replaces_key() {
+        let mut builder = AtlasBuilder::new([8, 8]);
+        builder.add("a", RgbaImage::new(2, 2));
+        builder.add("a", RgbaImage::new(3, 3));
+        assert_eq!(builder.images.len(), 1);
+        assert_eq!(builder.images[0].1.dimensions(), (3, 3));
+    }
+
+    #[test]
+    fn blit_extrudes_edges() {
+        let mut img = RgbaImage::new(2, 2);
+        img.put_pixel(0, 0, Rgba { data: [1, 0, 0, 255] });
+        img.put_pixel(1, 1, Rgba { data: [2, 0, 0, 255] });
+        let mut page = RgbaImage::new(6, 6);
+        blit(&mut page, &img, [2, 2], 1);
+        assert_eq!(page.get_pixel(2, 2), &Rgba { data: [1, 0, 0, 255] });
+        assert_eq!(page.get_pixel(1, 1), &Rgba { data: [1, 0, 0, 255] });
+        assert_eq!(page.get_pixel(4, 4), &Rgba { data: [2, 0, 0, 255] });
+        assert_eq!(page.get_pixel(0, 0), &Rgba { data: [0, 0, 0, 0] });
+        assert_eq!(page.get_pixel(5, 5), &Rgba { data: [0, 0, 0, 0] });
+    }
+}
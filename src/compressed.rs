@@ -0,0 +1,168 @@
+//! Block compressed texture data.
+
+use std::cmp;
+
+use texture_size;
+use Error;
+
+// ASTC block sizes in the order of the format enums of GL and Vulkan.
+pub const ASTC_BLOCKS: [(u8, u8); 14] = [
+    (4, 4), (5, 4), (5, 5), (6, 5), (6, 6), (8, 5), (8, 6),
+    (8, 8), (10, 5), (10, 6), (10, 8), (10, 10), (12, 10), (12, 12),
+];
+
+/// Block compressed storage format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressedFormat {
+    /// BC1 (DXT1), RGB with optional 1 bit alpha.
+    Bc1,
+    /// BC2 (DXT3), RGBA with explicit 4 bit alpha.
+    Bc2,
+    /// BC3 (DXT5), RGBA with interpolated alpha.
+    Bc3,
+    /// BC4, single channel.
+    Bc4,
+    /// BC5, two channels.
+    Bc5,
+    /// BC7, high quality RGBA.
+    Bc7,
+    /// ETC2 RGB.
+    Etc2Rgb8,
+    /// ETC2 RGBA.
+    Etc2Rgba8,
+    /// ASTC RGBA with the given block width and height in pixels.
+    ///
+    /// Only the 14 block sizes of 2D ASTC, from 4x4 to 12x12, are valid.
+    Astc(u8, u8),
+}
+
+impl CompressedFormat {
+    /// Returns `true` unless the format is ASTC with a block size
+    /// that is not defined by ASTC.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            CompressedFormat::Astc(w, h) => ASTC_BLOCKS.contains(&(w, h)),
+            _ => true,
+        }
+    }
+
+    /// Returns the width and height of a block in pixels.
+    pub fn block_size(&self) -> [u32; 2] {
+        match *self {
+            CompressedFormat::Astc(w, h) => [w as u32, h as u32],
+            _ => [4, 4],
+        }
+    }
+
+    /// Returns the number of bytes used by one block.
+    pub fn bytes_per_block(&self) -> usize {
+        match *self {
+            CompressedFormat::Bc1 |
+            CompressedFormat::Bc4 |
+            CompressedFormat::Etc2Rgb8 => 8,
+            _ => 16,
+        }
+    }
+
+    /// Returns the number of bytes of an image of the given size,
+    /// or `None` if the format is not valid or the image does not fit
+    /// in memory.
+    ///
+    /// Partial blocks at the right and bottom edges are stored whole.
+    pub fn level_len(&self, size: [u32; 2]) -> Option<usize> {
+        if !self.is_valid() {
+            return None;
+        }
+        let block = self.block_size();
+        let blocks_x = size[0] / block[0] + (size[0] % block[0] != 0) as u32;
+        let blocks_y = size[1] / block[1] + (size[1] % block[1] != 0) as u32;
+        (blocks_x as usize).checked_mul(blocks_y as usize)
+            .and_then(|blocks| blocks.checked_mul(self.bytes_per_block()))
+    }
+}
+
+/// Checks that there is at least one level, no more levels than in a
+/// full mipmap chain, and that each level has the length required
+/// by the format.
+///
+/// Returns `Error::Compressed` for an ASTC format with an invalid
+/// block size.
+///
+/// Gfx 0.10 has no compressed surface types, so compressed data can be
+/// validated but not uploaded as a texture.
+pub fn check_compressed_levels(
+    format: CompressedFormat,
+    levels: &[&[u8]],
+    size: [u32; 2]
+) -> Result<(), Error> {
+    if !format.is_valid() {
+        return Err(Error::Compressed(format));
+    }
+    try!(texture_size(size));
+    let max_levels = 32 - cmp::max(size[0], size[1]).leading_zeros();
+    if levels.is_empty() || levels.len() > max_levels as usize {
+        return Err(Error::Levels(levels.len()));
+    }
+    for (i, level) in levels.iter().enumerate() {
+        let level_size = [cmp::max(size[0] >> i, 1), cmp::max(size[1] >> i, 1)];
+        let expected = match format.level_len(level_size) {
+            Some(len) => len,
+            None => return Err(Error::Malformed("size does not fit in memory")),
+        };
+        if level.len() != expected {
+            return Err(Error::BufferSize {
+                expected: expected,
+                found: level.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_compressed_levels, CompressedFormat};
+    use Error;
+
+    #[test]
+    fn partial_blocks() {
+        assert_eq!(CompressedFormat::Bc1.level_len([5, 3]), Some(16));
+        assert_eq!(CompressedFormat::Bc3.level_len([1, 1]), Some(16));
+        assert_eq!(CompressedFormat::Astc(5, 4).level_len([6, 9]), Some(96));
+    }
+
+    #[test]
+    fn invalid_astc() {
+        assert_eq!(CompressedFormat::Astc(0, 4).level_len([4, 4]), None);
+        assert_eq!(CompressedFormat::Astc(7, 7).level_len([4, 4]), None);
+        let format = CompressedFormat::Astc(4, 0);
+        match check_compressed_levels(format, &[&[0; 16][..]], [4, 4]) {
+            Err(Error::Compressed(f)) => assert_eq!(f, format),
+            _ => panic!("expected a compressed format error"),
+        }
+    }
+
+    #[test]
+    fn level_count() {
+        let level: &[u8] = &[0; 8];
+        let format = CompressedFormat::Bc1;
+        assert!(check_compressed_levels(format, &[level; 3], [4, 4]).is_ok());
+        match check_compressed_levels(format, &[level; 4], [4, 4]) {
+            Err(Error::Levels(4)) => {}
+            _ => panic!("expected a levels error"),
+        }
+        match check_compressed_levels(format, &[], [4, 4]) {
+            Err(Error::Levels(0)) => {}
+            _ => panic!("expected a levels error"),
+        }
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let levels: [&[u8]; 2] = [&[0; 16], &[0; 7]];
+        match check_compressed_levels(CompressedFormat::Bc1, &levels, [8, 4]) {
+            Err(Error::BufferSize { expected: 8, found: 7 }) => {}
+            _ => panic!("expected a buffer size error"),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Texture data read from container files with mipmaps and layers.
+
+use std::cmp;
+use gfx;
+use texture::TextureSettings;
+
+use compressed::check_compressed_levels;
+use {dimension, texture_size};
+use {Alpha, CompressedFormat, Error, PixelFormat, Texture, Usage};
+
+/// Storage format of container data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfaceFormat {
+    /// Uncompressed pixels.
+    Pixel(PixelFormat),
+    /// Block compressed pixels.
+    Compressed(CompressedFormat),
+}
+
+/// Texture data with all mipmap levels, array layers and cube faces.
+pub struct Surface {
+    /// Storage format.
+    pub format: SurfaceFormat,
+    /// Width, height and depth of the first level.
+    pub size: [u32; 3],
+    /// Number of array layers, 1 when not an array.
+    pub layers: u32,
+    /// Whether each layer has six cube faces.
+    pub cube: bool,
+    /// Number of mipmap levels.
+    pub levels: u32,
+    /// One slice per level of each face of each layer, in that order.
+    pub data: Vec<Vec<u8>>,
+}
+
+impl Surface {
+    /// Returns the number of faces per layer.
+    pub fn faces(&self) -> u32 {
+        if self.cube { 6 } else { 1 }
+    }
+
+    /// Returns the size of a mipmap level.
+    pub fn level_size(&self, level: u32) -> [u32; 3] {
+        [cmp::max(self.size[0] >> level, 1),
+         cmp::max(self.size[1] >> level, 1),
+         cmp::max(self.size[2] >> level, 1)]
+    }
+
+    /// Returns the number of mipmap levels in a full chain.
+    pub fn max_levels(&self) -> u32 {
+        32 - cmp::max(cmp::max(self.size[0], self.size[1]), self.size[2])
+            .leading_zeros()
+    }
+
+    /// Returns the number of faces of all layers.
+    pub fn face_count(&self) -> Result<u32, Error> {
+        self.layers.checked_mul(self.faces())
+            .ok_or(Error::Malformed("too many layers"))
+    }
+
+    /// Returns the number of bytes of one face of a mipmap level.
+    pub fn level_len(&self, level: u32) -> Result<usize, Error> {
+        let size = self.level_size(level);
+        let slice = match self.format {
+            SurfaceFormat::Pixel(format) => (size[0] as usize)
+                .checked_mul(size[1] as usize)
+                .and_then(|len| len.checked_mul(format.bytes_per_pixel())),
+            SurfaceFormat::Compressed(format) =>
+                format.level_len([size[0], size[1]]),
+        };
+        slice.and_then(|len| len.checked_mul(size[2] as usize))
+            .ok_or(Error::Malformed("size does not fit in memory"))
+    }
+
+    /// Checks the number of levels against a full mipmap chain.
+    pub fn check_levels(&self) -> Result<(), Error> {
+        if self.levels == 0 || self.levels > self.max_levels() {
+            Err(Error::Levels(self.levels as usize))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Splits tightly packed memory ordered by layer, face and level
+    /// into slices.
+    ///
+    /// Returns the number of bytes read.
+    pub fn read_slices(&mut self, memory: &[u8]) -> Result<usize, Error> {
+        try!(self.check_levels());
+        let mut offset: usize = 0;
+        for _ in 0..try!(self.face_count()) {
+            for level in 0..self.levels {
+                let len = try!(self.level_len(level));
+                let end = match offset.checked_add(len) {
+                    Some(end) if end <= memory.len() => end,
+                    _ => return Err(Error::BufferSize {
+                        expected: offset.saturating_add(len),
+                        found: memory.len(),
+                    }),
+                };
+                self.data.push(memory[offset..end].to_vec());
+                offset = end;
+            }
+        }
+        Ok(offset)
+    }
+}
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a texture of the kind described by container data.
+    ///
+    /// Returns `Error::Compressed` for block compressed data,
+    /// which Gfx 0.10 can not upload.
+    pub fn from_surface<F>(
+        factory: &mut F,
+        surface: &Surface,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let size = surface.size;
+        let (width, height) = try!(texture_size([size[0], size[1]]));
+        try!(surface.check_levels());
+        if surface.layers == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let count = (try!(surface.face_count()) as usize)
+            .checked_mul(surface.levels as usize);
+        if count != Some(surface.data.len()) {
+            return Err(Error::Malformed("slices do not match the layers"));
+        }
+        if surface.cube && size[0] != size[1] {
+            return Err(Error::SizeMismatch {
+                expected: [size[0], size[0]],
+                found: [size[0], size[1]],
+            });
+        }
+        if size[2] > 1 && (surface.cube || surface.layers > 1) {
+            return Err(Error::Malformed("3D textures can not have layers"));
+        }
+        let format = match surface.format {
+            SurfaceFormat::Pixel(format) => format,
+            SurfaceFormat::Compressed(format) => {
+                let levels: Vec<&[u8]> = surface.data.iter()
+                    .take(surface.levels as usize).map(|s| &s[..]).collect();
+                try!(check_compressed_levels(format, &levels,
+                                             [size[0], size[1]]));
+                return Err(Error::Compressed(format));
+            }
+        };
+        for (i, slice) in surface.data.iter().enumerate() {
+            let expected = try!(surface.level_len(i as u32 % surface.levels));
+            if slice.len() != expected {
+                return Err(Error::BufferSize {
+                    expected: expected,
+                    found: slice.len(),
+                });
+            }
+        }
+
+        let layers = try!(dimension(surface.layers));
+        let kind = match (surface.cube, size[2] > 1, layers > 1) {
+            (true, _, false) => gfx::tex::Kind::Cube(width),
+            (true, _, true) => gfx::tex::Kind::CubeArray(width, layers),
+            (false, true, _) =>
+                gfx::tex::Kind::D3(width, height, try!(dimension(size[2]))),
+            (false, false, true) => gfx::tex::Kind::D2Array(width, height,
+                layers, gfx::tex::AaMode::Single),
+            (false, false, false) =>
+                gfx::tex::Kind::D2(width, height, gfx::tex::AaMode::Single),
+        };
+        let data: Vec<&[u8]> = surface.data.iter().map(|s| &s[..]).collect();
+        Texture::create_surface(factory, kind, format, &data,
+                                surface.levels as gfx::tex::Level,
+                                Alpha::Straight, Usage::Immutable, settings)
+    }
+}
+
+/// Reads a little endian `u32` at the given byte offset.
+pub fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    bytes[offset] as u32 |
+    (bytes[offset + 1] as u32) << 8 |
+    (bytes[offset + 2] as u32) << 16 |
+    (bytes[offset + 3] as u32) << 24
+}
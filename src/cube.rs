@@ -0,0 +1,127 @@
+//! Cube map textures.
+
+use gfx;
+use image::RgbaImage;
+use texture::TextureSettings;
+
+use dimension;
+use {Alpha, Error, Flip, PixelFormat, Texture, Usage};
+
+/// Layout of cube faces within a single image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeLayout {
+    /// Faces in a 4x3 grid, with the cross lying on its side:
+    ///
+    /// ```text
+    ///     +Y
+    /// -X  +Z  +X  -Z
+    ///     -Y
+    /// ```
+    HorizontalCross,
+    /// Faces in a 3x4 grid, with `-Z` upside down at the bottom:
+    ///
+    /// ```text
+    ///     +Y
+    /// -X  +Z  +X
+    ///     -Y
+    ///     -Z
+    /// ```
+    VerticalCross,
+}
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a cube map from six square faces of equal size.
+    ///
+    /// Faces are ordered `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`,
+    /// and each face is flipped with its own setting.
+    pub fn from_cube_faces<F>(
+        factory: &mut F,
+        faces: &[RgbaImage; 6],
+        flips: [Flip; 6],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (size, _) = faces[0].dimensions();
+        let mut memory = Vec::with_capacity(6);
+        for (face, &flip) in faces.iter().zip(flips.iter()) {
+            let (w, h) = face.dimensions();
+            if [w, h] != [size, size] {
+                return Err(Error::SizeMismatch {
+                    expected: [size, size],
+                    found: [w, h],
+                });
+            }
+            memory.push(flip.apply_memory(face, [w, h], 4).into_owned());
+        }
+        Texture::create_cube(factory, size, &memory, settings)
+    }
+
+    /// Creates a cube map from an image with faces laid out as a cross.
+    pub fn from_cube_cross<F>(
+        factory: &mut F,
+        img: &RgbaImage,
+        layout: CubeLayout,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (w, h) = img.dimensions();
+        let (size, expected) = match layout {
+            CubeLayout::HorizontalCross => (w / 4, [w / 4 * 4, w / 4 * 3]),
+            CubeLayout::VerticalCross => (w / 3, [w / 3 * 3, w / 3 * 4]),
+        };
+        if [w, h] != expected {
+            return Err(Error::SizeMismatch {
+                expected: expected,
+                found: [w, h],
+            });
+        }
+
+        // Grid cells of `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`.
+        let (cells, neg_z_flip) = match layout {
+            CubeLayout::HorizontalCross =>
+                ([[2, 1], [0, 1], [1, 0], [1, 2], [1, 1], [3, 1]], Flip::None),
+            CubeLayout::VerticalCross =>
+                ([[2, 1], [0, 1], [1, 0], [1, 2], [1, 1], [1, 3]], Flip::Both),
+        };
+        let mut memory = Vec::with_capacity(6);
+        for (i, cell) in cells.iter().enumerate() {
+            let face = crop(img, [cell[0] * size, cell[1] * size], size);
+            let flip = if i == 5 { neg_z_flip } else { Flip::None };
+            memory.push(flip.apply_memory(&face, [size, size], 4).into_owned());
+        }
+        Texture::create_cube(factory, size, &memory, settings)
+    }
+
+    fn create_cube<F>(
+        factory: &mut F,
+        size: u32,
+        faces: &[Vec<u8>],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        if size == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let kind = gfx::tex::Kind::Cube(try!(dimension(size)));
+        let data: Vec<&[u8]> = faces.iter().map(|face| &face[..]).collect();
+        Texture::create_surface(factory, kind, PixelFormat::Srgba8,
+                                &data, 1, Alpha::Straight, Usage::Immutable,
+                                settings)
+    }
+}
+
+/// Copies a square of pixels out of an image.
+fn crop(img: &RgbaImage, pos: [u32; 2], size: u32) -> Vec<u8> {
+    let pixels: &[u8] = img;
+    let row = img.width() as usize * 4;
+    let (x, y, size) = (pos[0] as usize, pos[1] as usize, size as usize);
+    let mut memory = Vec::with_capacity(size * size * 4);
+    for j in y..y + size {
+        let start = j * row + x * 4;
+        memory.extend_from_slice(&pixels[start..start + size * 4]);
+    }
+    memory
+}
@@ -0,0 +1,293 @@
+//! DirectDraw Surface files.
+
+use std::cmp;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use gfx;
+use texture::TextureSettings;
+
+use container::read_u32;
+use {CompressedFormat, Error, PixelFormat, Surface, SurfaceFormat, Texture};
+
+// "DDS " followed by the size of the header.
+const MAGIC: u32 = 0x2053_4444;
+const HEADER_LEN: usize = 128;
+const DX10_HEADER_LEN: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x2_0000;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xfc00;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+const DIMENSION_TEXTURE3D: u32 = 4;
+const MISC_TEXTURECUBE: u32 = 0x4;
+
+// Four character codes read as little endian integers.
+const DXT1: u32 = 0x3154_5844;
+const DXT2: u32 = 0x3254_5844;
+const DXT3: u32 = 0x3354_5844;
+const DXT4: u32 = 0x3454_5844;
+const DXT5: u32 = 0x3554_5844;
+const ATI1: u32 = 0x3149_5441;
+const BC4U: u32 = 0x5534_4342;
+const ATI2: u32 = 0x3249_5441;
+const BC5U: u32 = 0x5535_4342;
+const DX10: u32 = 0x3031_5844;
+const A16B16G16R16F: u32 = 113;
+const A32B32G32R32F: u32 = 116;
+
+/// How 32 bit pixels are converted to RGBA after reading.
+struct Order {
+    // Red and blue are swapped in the file.
+    bgr: bool,
+    // Alpha is unused in the file.
+    opaque: bool,
+}
+
+const RGBA: Order = Order { bgr: false, opaque: false };
+
+impl Surface {
+    /// Reads a DDS file with a DX9 or DX10 header.
+    ///
+    /// BGRA pixels are reordered to RGBA.
+    /// Uncompressed DX9 color data is read as sRGB.
+    pub fn from_dds(bytes: &[u8]) -> Result<Surface, Error> {
+        if bytes.len() < HEADER_LEN || read_u32(bytes, 0) != MAGIC ||
+           read_u32(bytes, 4) != 124 {
+            return Err(Error::Malformed("not a DDS file"));
+        }
+        let flags = read_u32(bytes, 8);
+        let height = read_u32(bytes, 12);
+        let width = read_u32(bytes, 16);
+        let depth = cmp::max(read_u32(bytes, 24), 1);
+        let levels = if flags & DDSD_MIPMAPCOUNT != 0 {
+            cmp::max(read_u32(bytes, 28), 1)
+        } else {
+            1
+        };
+        let caps2 = read_u32(bytes, 112);
+
+        let mut surface = Surface {
+            format: SurfaceFormat::Pixel(PixelFormat::Srgba8),
+            size: [width, height, 1],
+            layers: 1,
+            cube: false,
+            levels: levels,
+            data: vec![],
+        };
+        let order;
+        let offset;
+        if read_u32(bytes, 80) & DDPF_FOURCC != 0 &&
+           read_u32(bytes, 84) == DX10 {
+            if bytes.len() < HEADER_LEN + DX10_HEADER_LEN {
+                return Err(Error::Malformed("DX10 header is truncated"));
+            }
+            let (format, dx10_order) = try!(dxgi_format(read_u32(bytes, 128)));
+            surface.format = format;
+            order = dx10_order;
+            if read_u32(bytes, 132) == DIMENSION_TEXTURE3D {
+                surface.size[2] = depth;
+            }
+            surface.cube = read_u32(bytes, 136) & MISC_TEXTURECUBE != 0;
+            surface.layers = cmp::max(read_u32(bytes, 140), 1);
+            offset = HEADER_LEN + DX10_HEADER_LEN;
+        } else {
+            let (format, dx9_order) = try!(dx9_format(bytes));
+            surface.format = format;
+            order = dx9_order;
+            if caps2 & DDSCAPS2_VOLUME != 0 {
+                surface.size[2] = depth;
+            }
+            if caps2 & DDSCAPS2_CUBEMAP != 0 {
+                if caps2 & DDSCAPS2_CUBEMAP_ALLFACES !=
+                   DDSCAPS2_CUBEMAP_ALLFACES {
+                    return Err(Error::Malformed("cube map is missing faces"));
+                }
+                surface.cube = true;
+            }
+            offset = HEADER_LEN;
+        }
+
+        try!(surface.read_slices(&bytes[offset..]));
+        if order.bgr || order.opaque {
+            for slice in &mut surface.data {
+                for pixel in slice.chunks_mut(4) {
+                    if order.bgr { pixel.swap(0, 2); }
+                    if order.opaque { pixel[3] = 255; }
+                }
+            }
+        }
+        Ok(surface)
+    }
+}
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a texture from the bytes of a DDS file,
+    /// with all mipmap levels, array layers and cube faces.
+    pub fn from_dds<F>(
+        factory: &mut F,
+        bytes: &[u8],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let surface = try!(Surface::from_dds(bytes));
+        Texture::from_surface(factory, &surface, settings)
+    }
+
+    /// Creates a texture from a DDS file.
+    pub fn from_dds_path<F, P>(
+        factory: &mut F,
+        path: P,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>,
+              P: AsRef<Path>
+    {
+        let mut bytes = vec![];
+        try!(try!(File::open(path)).read_to_end(&mut bytes));
+        Texture::from_dds(factory, &bytes, settings)
+    }
+}
+
+/// Maps the pixel format of a DX9 header.
+fn dx9_format(bytes: &[u8]) -> Result<(SurfaceFormat, Order), Error> {
+    let flags = read_u32(bytes, 80);
+    let bits = read_u32(bytes, 88);
+    let masks = [read_u32(bytes, 92), read_u32(bytes, 96),
+                 read_u32(bytes, 100)];
+    let alpha = flags & DDPF_ALPHAPIXELS != 0 && read_u32(bytes, 104) != 0;
+
+    if flags & DDPF_FOURCC != 0 {
+        let four_cc = read_u32(bytes, 84);
+        let format = match four_cc {
+            DXT1 => CompressedFormat::Bc1,
+            DXT2 | DXT3 => CompressedFormat::Bc2,
+            DXT4 | DXT5 => CompressedFormat::Bc3,
+            ATI1 | BC4U => CompressedFormat::Bc4,
+            ATI2 | BC5U => CompressedFormat::Bc5,
+            A16B16G16R16F =>
+                return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba16F), RGBA)),
+            A32B32G32R32F =>
+                return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba32F), RGBA)),
+            _ => return Err(Error::UnsupportedFormat(four_cc)),
+        };
+        Ok((SurfaceFormat::Compressed(format), RGBA))
+    } else if flags & DDPF_RGB != 0 && bits == 32 {
+        let bgr = if masks == [0xff, 0xff00, 0xff_0000] {
+            false
+        } else if masks == [0xff_0000, 0xff00, 0xff] {
+            true
+        } else {
+            return Err(Error::UnsupportedFormat(bits));
+        };
+        let order = Order { bgr: bgr, opaque: !alpha };
+        Ok((SurfaceFormat::Pixel(PixelFormat::Srgba8), order))
+    } else if flags & DDPF_LUMINANCE != 0 && bits == 8 && !alpha {
+        Ok((SurfaceFormat::Pixel(PixelFormat::R8), RGBA))
+    } else {
+        Err(Error::UnsupportedFormat(bits))
+    }
+}
+
+/// Maps the DXGI format of a DX10 header.
+fn dxgi_format(format: u32) -> Result<(SurfaceFormat, Order), Error> {
+    let bgra = Order { bgr: true, opaque: false };
+    let bgrx = Order { bgr: true, opaque: true };
+    let (format, order) = match format {
+        2 => (SurfaceFormat::Pixel(PixelFormat::Rgba32F), RGBA),
+        10 => (SurfaceFormat::Pixel(PixelFormat::Rgba16F), RGBA),
+        28 => (SurfaceFormat::Pixel(PixelFormat::Rgba8), RGBA),
+        29 => (SurfaceFormat::Pixel(PixelFormat::Srgba8), RGBA),
+        49 => (SurfaceFormat::Pixel(PixelFormat::Rg8), RGBA),
+        61 => (SurfaceFormat::Pixel(PixelFormat::R8), RGBA),
+        87 => (SurfaceFormat::Pixel(PixelFormat::Rgba8), bgra),
+        88 => (SurfaceFormat::Pixel(PixelFormat::Rgba8), bgrx),
+        91 => (SurfaceFormat::Pixel(PixelFormat::Srgba8), bgra),
+        93 => (SurfaceFormat::Pixel(PixelFormat::Srgba8), bgrx),
+        71 | 72 => (SurfaceFormat::Compressed(CompressedFormat::Bc1), RGBA),
+        74 | 75 => (SurfaceFormat::Compressed(CompressedFormat::Bc2), RGBA),
+        77 | 78 => (SurfaceFormat::Compressed(CompressedFormat::Bc3), RGBA),
+        80 => (SurfaceFormat::Compressed(CompressedFormat::Bc4), RGBA),
+        83 => (SurfaceFormat::Compressed(CompressedFormat::Bc5), RGBA),
+        98 | 99 => (SurfaceFormat::Compressed(CompressedFormat::Bc7), RGBA),
+        _ => return Err(Error::UnsupportedFormat(format)),
+    };
+    Ok((format, order))
+}
+
+#[cfg(test)]
+mod tests {
+    use {CompressedFormat, Error, PixelFormat, Surface, SurfaceFormat};
+
+    #[test]
+    fn bgra_mipmaps() {
+        let bytes = include_bytes!("../tests/fixtures/bgra8_mips.dds");
+        let surface = Surface::from_dds(bytes).unwrap();
+        assert_eq!(surface.format, SurfaceFormat::Pixel(PixelFormat::Srgba8));
+        assert_eq!(surface.size, [4, 2, 1]);
+        assert_eq!(surface.levels, 3);
+        let lens: Vec<usize> = surface.data.iter().map(|s| s.len()).collect();
+        assert_eq!(lens, vec![32, 8, 4]);
+        assert_eq!(&surface.data[2][..], &[3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn dxt1_cube() {
+        let bytes = include_bytes!("../tests/fixtures/dxt1_cube.dds");
+        let surface = Surface::from_dds(bytes).unwrap();
+        assert_eq!(surface.format,
+                   SurfaceFormat::Compressed(CompressedFormat::Bc1));
+        assert!(surface.cube);
+        assert_eq!(surface.data.len(), 6);
+        assert_eq!(&surface.data[5][..], &[5; 8]);
+    }
+
+    #[test]
+    fn dx10_array() {
+        let bytes = include_bytes!("../tests/fixtures/r8_array.dds");
+        let surface = Surface::from_dds(bytes).unwrap();
+        assert_eq!(surface.format, SurfaceFormat::Pixel(PixelFormat::R8));
+        assert_eq!(surface.layers, 3);
+        assert_eq!(surface.levels, 2);
+        assert_eq!(&surface.data[4][..], &[21, 22, 23, 24]);
+        assert_eq!(&surface.data[5][..], &[25]);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = include_bytes!("../tests/fixtures/r8_array.dds");
+        match Surface::from_dds(&bytes[..bytes.len() - 1]) {
+            Err(Error::BufferSize { .. }) => {}
+            _ => panic!("expected a buffer size error"),
+        }
+        match Surface::from_dds(&bytes[..64]) {
+            Err(Error::Malformed(_)) => {}
+            _ => panic!("expected a malformed file error"),
+        }
+    }
+
+    #[test]
+    fn corrupt_header() {
+        let fixture = include_bytes!("../tests/fixtures/r8_array.dds");
+        let mut bytes = fixture.to_vec();
+        // A cube map array with 0xffffffff layers.
+        bytes[136] = 0x4;
+        for byte in &mut bytes[140..144] { *byte = 0xff; }
+        match Surface::from_dds(&bytes) {
+            Err(Error::Malformed(_)) => {}
+            _ => panic!("expected a malformed file error"),
+        }
+        // More levels than in a full mipmap chain of a 2x2 texture.
+        let mut bytes = fixture.to_vec();
+        bytes[28] = 3;
+        match Surface::from_dds(&bytes) {
+            Err(Error::Levels(3)) => {}
+            _ => panic!("expected a levels error"),
+        }
+    }
+}
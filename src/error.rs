@@ -0,0 +1,178 @@
+//! Errors when creating or updating textures.
+
+use std::error;
+use std::fmt;
+use std::io;
+use gfx;
+use gfx::CombinedError;
+use image::ImageError;
+
+use {CompressedFormat, Usage};
+
+/// An error when creating or updating a texture.
+///
+/// Errors from Gfx 0.10 do not implement `std::error::Error`, so
+/// `Gfx`, `Target` and `Update` have no `source` and are told apart
+/// by matching on the variant and the Gfx error it holds.
+#[derive(Debug)]
+pub enum Error {
+    /// Width or height is zero.
+    ZeroSize,
+    /// Width or height is larger than supported by Gfx.
+    DimensionOverflow(u32),
+    /// Images that should have equal size differ.
+    SizeMismatch {
+        /// Expected size.
+        expected: [u32; 2],
+        /// Size found.
+        found: [u32; 2],
+    },
+    /// Image of the given size does not fit in the atlas.
+    DoesNotFit([u32; 2]),
+    /// Region to update does not lie inside the texture.
+    OutOfBounds {
+        /// Offset of the region.
+        offset: [u32; 3],
+        /// Size of the region.
+        size: [u32; 3],
+    },
+    /// Length of memory does not match the texture size.
+    BufferSize {
+        /// Expected number of bytes.
+        expected: usize,
+        /// Number of bytes found.
+        found: usize,
+    },
+    /// Image file could not be read.
+    Io(io::Error),
+    /// Image format or color type is not supported.
+    Unsupported(ImageError),
+    /// Image could not be decoded.
+    Decode(ImageError),
+    /// Number of mipmap levels is zero or larger than a full chain.
+    Levels(usize),
+    /// Block compressed format is not supported by Gfx.
+    Compressed(CompressedFormat),
+    /// KTX2 file uses the given supercompression scheme.
+    Supercompressed(u32),
+    /// Container file is malformed or inconsistent.
+    Malformed(&'static str),
+    /// Pixel format of a container file is not supported,
+    /// identified by its code or bit count in the file.
+    UnsupportedFormat(u32),
+    /// Streaming texture has fewer than two textures to rotate between.
+    StreamingCount(usize),
+    /// Texture was not created with `Usage::Dynamic` and can not be updated.
+    NotDynamic(Usage),
+    /// Gfx failed to create the texture.
+    Gfx(CombinedError),
+    /// Gfx failed to create a render target view.
+    Target(gfx::TargetViewError),
+    /// Gfx failed to update the texture.
+    Update(gfx::UpdateError<[u16; 3]>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::ZeroSize => write!(f, "Texture has zero size"),
+            Error::DimensionOverflow(size) =>
+                write!(f, "Texture dimension {} is too large", size),
+            Error::SizeMismatch { expected, found } =>
+                write!(f, "Expected image of size {}x{}, found {}x{}",
+                       expected[0], expected[1], found[0], found[1]),
+            Error::DoesNotFit(size) =>
+                write!(f, "Image of size {}x{} does not fit in the atlas",
+                       size[0], size[1]),
+            Error::OutOfBounds { offset, size } => write!(f,
+                "Region {}x{}x{} at ({}, {}, {}) is outside the texture",
+                size[0], size[1], size[2], offset[0], offset[1], offset[2]),
+            Error::BufferSize { expected, found } =>
+                write!(f, "Expected {} bytes of texture memory, found {}",
+                       expected, found),
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Unsupported(ref err) =>
+                write!(f, "Unsupported image: {}", err),
+            Error::Decode(ref err) =>
+                write!(f, "Could not decode image: {}", err),
+            Error::Levels(count) =>
+                write!(f, "Invalid number of mipmap levels: {}", count),
+            Error::Compressed(format) =>
+                write!(f, "Compressed format {:?} is not supported", format),
+            Error::Supercompressed(scheme) => write!(f,
+                "Supercompression scheme {} is not supported", scheme),
+            Error::Malformed(reason) =>
+                write!(f, "Malformed texture file: {}", reason),
+            Error::UnsupportedFormat(code) =>
+                write!(f, "Unsupported pixel format {:#x}", code),
+            Error::StreamingCount(count) => write!(f,
+                "Streaming texture needs at least 2 textures, found {}", count),
+            Error::NotDynamic(usage) =>
+                write!(f, "Texture with usage {:?} can not be updated", usage),
+            Error::Gfx(ref err) =>
+                write!(f, "Gfx failed to create the texture: {:?}", err),
+            Error::Target(ref err) =>
+                write!(f, "Gfx failed to create a render target: {:?}", err),
+            Error::Update(ref err) =>
+                write!(f, "Gfx failed to update the texture: {:?}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Unsupported(ref err) |
+            Error::Decode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(err: ImageError) -> Error {
+        match err {
+            ImageError::IoError(err) => Error::Io(err),
+            err @ ImageError::UnsupportedError(_) |
+            err @ ImageError::UnsupportedColor(_) => Error::Unsupported(err),
+            err => Error::Decode(err),
+        }
+    }
+}
+
+impl From<CombinedError> for Error {
+    fn from(err: CombinedError) -> Error {
+        Error::Gfx(err)
+    }
+}
+
+impl From<gfx::tex::Error> for Error {
+    fn from(err: gfx::tex::Error) -> Error {
+        Error::Gfx(err.into())
+    }
+}
+
+impl From<gfx::ResourceViewError> for Error {
+    fn from(err: gfx::ResourceViewError) -> Error {
+        Error::Gfx(err.into())
+    }
+}
+
+impl From<gfx::TargetViewError> for Error {
+    fn from(err: gfx::TargetViewError) -> Error {
+        Error::Target(err)
+    }
+}
+
+impl From<gfx::UpdateError<[u16; 3]>> for Error {
+    fn from(err: gfx::UpdateError<[u16; 3]>) -> Error {
+        Error::Update(err)
+    }
+}
@@ -0,0 +1,130 @@
+//! Flipping and rotating of pixel memory.
+
+use std::borrow::Cow;
+use image::{ImageBuffer, RgbaImage};
+
+/// Flip settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flip {
+    /// Does not flip.
+    None,
+    /// Flips image vertically.
+    Vertical,
+    /// Flips image horizontally.
+    Horizontal,
+    /// Flips image both vertically and horizontally.
+    Both,
+    /// Rotates image 90 degrees clockwise.
+    Rotate90,
+    /// Rotates image 270 degrees clockwise.
+    Rotate270,
+}
+
+impl Flip {
+    /// Returns the size of an image after the flip.
+    pub fn size(self, size: [u32; 2]) -> [u32; 2] {
+        match self {
+            Flip::Rotate90 | Flip::Rotate270 => [size[1], size[0]],
+            _ => size,
+        }
+    }
+
+    /// Returns the image with the flip applied.
+    pub fn apply(self, img: &RgbaImage) -> RgbaImage {
+        let (width, height) = img.dimensions();
+        let size = self.size([width, height]);
+        let memory = self.apply_memory(img, [width, height], 4).into_owned();
+        ImageBuffer::from_raw(size[0], size[1], memory)
+            .expect("Flipped image should have the same number of pixels")
+    }
+
+    /// Reorders pixel memory of the given size.
+    ///
+    /// Vertical flips copy whole rows, and no copy is made when not flipping.
+    /// The memory must hold `size[0] * size[1] * bytes_per_pixel` bytes.
+    pub fn apply_memory<'a>(
+        self,
+        memory: &'a [u8],
+        size: [u32; 2],
+        bytes_per_pixel: usize
+    ) -> Cow<'a, [u8]> {
+        let (width, height) = (size[0] as usize, size[1] as usize);
+        let bpp = bytes_per_pixel;
+        match self {
+            Flip::None => return Cow::Borrowed(memory),
+            Flip::Vertical => {
+                let row = width * bpp;
+                let mut flipped = Vec::with_capacity(memory.len());
+                for y in (0..height).rev() {
+                    flipped.extend_from_slice(&memory[y * row..(y + 1) * row]);
+                }
+                return Cow::Owned(flipped);
+            }
+            _ => {}
+        }
+
+        let new_width = self.size(size)[0] as usize;
+        let mut flipped = vec![0; memory.len()];
+        for (i, dst) in flipped.chunks_mut(bpp).enumerate() {
+            let (x, y) = (i % new_width, i / new_width);
+            let (src_x, src_y) = match self {
+                Flip::Horizontal => (width - 1 - x, y),
+                Flip::Both => (width - 1 - x, height - 1 - y),
+                Flip::Rotate90 => (y, height - 1 - x),
+                Flip::Rotate270 => (width - 1 - y, x),
+                Flip::None | Flip::Vertical => (x, y),
+            };
+            let src = (src_y * width + src_x) * bpp;
+            dst.copy_from_slice(&memory[src..src + bpp]);
+        }
+        Cow::Owned(flipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{imageops, ImageBuffer, Luma, Pixel, Rgba};
+    use super::Flip;
+
+    const FLIPS: [Flip; 6] = [Flip::None, Flip::Vertical, Flip::Horizontal,
+                              Flip::Both, Flip::Rotate90, Flip::Rotate270];
+
+    fn expected<P>(flip: Flip, img: &ImageBuffer<P, Vec<u8>>) -> Vec<u8>
+        where P: Pixel<Subpixel = u8> + 'static
+    {
+        match flip {
+            Flip::None => img.to_vec(),
+            Flip::Vertical => imageops::flip_vertical(img).into_raw(),
+            Flip::Horizontal => imageops::flip_horizontal(img).into_raw(),
+            Flip::Both => imageops::rotate180(img).into_raw(),
+            Flip::Rotate90 => imageops::rotate90(img).into_raw(),
+            Flip::Rotate270 => imageops::rotate270(img).into_raw(),
+        }
+    }
+
+    #[test]
+    fn one_byte_per_pixel() {
+        let memory: Vec<u8> = (0..6).collect();
+        let img: ImageBuffer<Luma<u8>, _> =
+            ImageBuffer::from_raw(3, 2, memory.clone()).unwrap();
+        for &flip in &FLIPS {
+            let flipped = flip.apply_memory(&memory, [3, 2], 1);
+            assert_eq!(&flipped[..], &expected(flip, &img)[..], "{:?}", flip);
+        }
+    }
+
+    #[test]
+    fn four_bytes_per_pixel() {
+        let memory: Vec<u8> = (0..24).collect();
+        let img: ImageBuffer<Rgba<u8>, _> =
+            ImageBuffer::from_raw(3, 2, memory.clone()).unwrap();
+        for &flip in &FLIPS {
+            let flipped = flip.apply_memory(&memory, [3, 2], 4);
+            assert_eq!(&flipped[..], &expected(flip, &img)[..], "{:?}", flip);
+            let applied = flip.apply(&img);
+            let (width, height) = applied.dimensions();
+            assert_eq!([width, height], flip.size([3, 2]));
+            assert_eq!(applied.into_raw(), expected(flip, &img));
+        }
+    }
+}
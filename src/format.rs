@@ -0,0 +1,98 @@
+//! Storage formats for textures.
+
+use gfx;
+use gfx::format::{ChannelType, SurfaceType};
+use texture::Format;
+
+/// Color space of texture data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Values are gamma encoded and converted to linear when sampled.
+    Srgb,
+    /// Values are sampled as stored.
+    Linear,
+}
+
+/// Storage format of texture data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Linear `(red, green, blue, alpha)` with values 0-255.
+    Rgba8,
+    /// sRGB `(red, green, blue, alpha)` with values 0-255.
+    Srgba8,
+    /// Single linear channel with values 0-255.
+    R8,
+    /// Two linear channels with values 0-255.
+    Rg8,
+    /// `(red, green, blue, alpha)` as 16 bit floats.
+    Rgba16F,
+    /// `(red, green, blue, alpha)` as 32 bit floats.
+    Rgba32F,
+}
+
+impl PixelFormat {
+    /// Returns the number of bytes used by one pixel.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match *self {
+            PixelFormat::R8 => 1,
+            PixelFormat::Rg8 => 2,
+            PixelFormat::Rgba8 | PixelFormat::Srgba8 => 4,
+            PixelFormat::Rgba16F => 8,
+            PixelFormat::Rgba32F => 16,
+        }
+    }
+
+    /// Returns the color space of the format.
+    pub fn color_space(&self) -> ColorSpace {
+        match *self {
+            PixelFormat::Srgba8 => ColorSpace::Srgb,
+            _ => ColorSpace::Linear,
+        }
+    }
+
+    /// Returns the format with the same storage in the given color space.
+    ///
+    /// Formats without an sRGB variant are returned unchanged.
+    pub fn with_color_space(self, color_space: ColorSpace) -> PixelFormat {
+        match (self, color_space) {
+            (PixelFormat::Rgba8, ColorSpace::Srgb) => PixelFormat::Srgba8,
+            (PixelFormat::Srgba8, ColorSpace::Linear) => PixelFormat::Rgba8,
+            (format, _) => format,
+        }
+    }
+
+    /// Returns the Gfx surface type.
+    pub fn surface_type(&self) -> SurfaceType {
+        match *self {
+            PixelFormat::R8 => SurfaceType::R8,
+            PixelFormat::Rg8 => SurfaceType::R8_G8,
+            PixelFormat::Rgba8 | PixelFormat::Srgba8 =>
+                SurfaceType::R8_G8_B8_A8,
+            PixelFormat::Rgba16F => SurfaceType::R16_G16_B16_A16,
+            PixelFormat::Rgba32F => SurfaceType::R32_G32_B32_A32,
+        }
+    }
+
+    /// Returns the Gfx channel type used by the shader view.
+    pub fn channel_type(&self) -> ChannelType {
+        match *self {
+            PixelFormat::Srgba8 => ChannelType::Srgb,
+            PixelFormat::R8 | PixelFormat::Rg8 | PixelFormat::Rgba8 =>
+                ChannelType::Unorm,
+            PixelFormat::Rgba16F | PixelFormat::Rgba32F => ChannelType::Float,
+        }
+    }
+
+    /// Returns the Gfx format.
+    pub fn gfx_format(&self) -> gfx::format::Format {
+        gfx::format::Format(self.surface_type(), self.channel_type())
+    }
+}
+
+impl From<Format> for PixelFormat {
+    fn from(format: Format) -> PixelFormat {
+        match format {
+            Format::Rgba8 => PixelFormat::Srgba8,
+        }
+    }
+}
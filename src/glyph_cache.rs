@@ -0,0 +1,314 @@
+//! Glyph cache packing alpha coverage into a shared texture.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::hash::Hash;
+use gfx;
+use gfx::format::{ChannelSource, Swizzle};
+use gfx_core::factory::Typed;
+use texture::{ImageSize, TextureSettings};
+
+use atlas::Region;
+use {Alpha, Error, Flip, PixelFormat, Texture, Usage};
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    // Start of unused space at the end of the shelf.
+    x: u32,
+    // Slots `[x, width]` left by evicted glyphs.
+    free: Vec<[u32; 2]>,
+}
+
+struct Entry {
+    rect: [u32; 4],
+    shelf: usize,
+    slot: [u32; 2],
+    last_used: u64,
+    frame: u64,
+}
+
+/// Caches alpha coverage bitmaps of glyphs in a single channel texture.
+///
+/// The texture grows up to a maximum size when full,
+/// after which the least recently used glyphs are evicted.
+/// Growing replaces `texture` and changes its size, which invalidates
+/// the texture coordinates of regions returned earlier,
+/// so look them up again with `get` after inserting.
+/// Glyphs used in the current frame are never evicted,
+/// so call `next_frame` once per frame.
+/// Coverage is sampled as alpha with white color channels.
+pub struct GlyphCache<R, K> where R: gfx::Resources {
+    /// Texture holding the glyphs.
+    pub texture: Texture<R>,
+    pixels: Vec<u8>,
+    size: [u32; 2],
+    max_size: [u32; 2],
+    settings: TextureSettings,
+    shelves: Vec<Shelf>,
+    entries: HashMap<K, Entry>,
+    tick: u64,
+    frame: u64,
+}
+
+impl<R, K> GlyphCache<R, K>
+    where R: gfx::Resources,
+          K: Hash + Eq + Clone
+{
+    /// Creates a new glyph cache.
+    pub fn new<F>(
+        factory: &mut F,
+        size: [u32; 2],
+        max_size: [u32; 2],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let pixels = vec![0; size[0] as usize * size[1] as usize];
+        let texture = try!(create_texture(factory, &pixels, size, settings));
+        Ok(GlyphCache {
+            texture: texture,
+            pixels: pixels,
+            size: size,
+            max_size: [cmp::max(size[0], max_size[0]),
+                       cmp::max(size[1], max_size[1])],
+            settings: *settings,
+            shelves: vec![],
+            entries: HashMap::new(),
+            tick: 0,
+            frame: 0,
+        })
+    }
+
+    /// Starts a new frame, allowing glyphs used only in earlier frames
+    /// to be evicted.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Returns the region of a cached glyph and marks it as used.
+    pub fn get(&mut self, key: &K) -> Option<Region> {
+        let size = self.size;
+        self.tick += 1;
+        let (tick, frame) = (self.tick, self.frame);
+        self.entries.get_mut(key).map(|entry| {
+            entry.last_used = tick;
+            entry.frame = frame;
+            Region::new(0, entry.rect, size)
+        })
+    }
+
+    /// Inserts a glyph from alpha coverage and returns its region.
+    ///
+    /// Returns the existing region if the glyph is already cached.
+    /// The texture may grow, after which regions returned earlier
+    /// are no longer valid.
+    /// Returns `Error::DoesNotFit` if the glyph is larger than the maximum
+    /// size, or if there is no room left without evicting glyphs used
+    /// in the current frame.
+    pub fn insert<F, C>(
+        &mut self,
+        factory: &mut F,
+        encoder: &mut gfx::Encoder<R, C>,
+        key: K,
+        coverage: &[u8],
+        size: [u32; 2]
+    ) -> Result<Region, Error>
+        where F: gfx::Factory<R>,
+              C: gfx::CommandBuffer<R>
+    {
+        if let Some(region) = self.get(&key) {
+            return Ok(region);
+        }
+        if size[0] == 0 || size[1] == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let expected = size[0] as usize * size[1] as usize;
+        if coverage.len() != expected {
+            return Err(Error::BufferSize {
+                expected: expected,
+                found: coverage.len(),
+            });
+        }
+
+        // One pixel of padding keeps neighbours from bleeding in.
+        let padded = [size[0] + 1, size[1] + 1];
+        if padded[0] > self.max_size[0] || padded[1] > self.max_size[1] {
+            return Err(Error::DoesNotFit(size));
+        }
+        let (shelf, slot) = loop {
+            if let Some(found) = self.allocate(padded) {
+                break found;
+            }
+            if self.size[0] < self.max_size[0] ||
+               self.size[1] < self.max_size[1] {
+                try!(self.grow(factory));
+            } else if self.evict() {
+                continue;
+            } else if self.entries.is_empty() && !self.shelves.is_empty() {
+                self.shelves.clear();
+            } else {
+                return Err(Error::DoesNotFit(size));
+            }
+        };
+
+        // Upload the whole slot, so that no coverage of an evicted glyph
+        // is left in the padding for filtering to pick up.
+        let rect = [slot[0], self.shelves[shelf].y, size[0], size[1]];
+        let (width, height) = (slot[1] as usize,
+                               self.shelves[shelf].height as usize);
+        let mut block = vec![0; width * height];
+        for y in 0..size[1] as usize {
+            let src = y * size[0] as usize;
+            block[y * width..y * width + size[0] as usize]
+                .copy_from_slice(&coverage[src..src + size[0] as usize]);
+        }
+        for y in 0..height {
+            let dst = (rect[1] as usize + y) * self.size[0] as usize +
+                      rect[0] as usize;
+            self.pixels[dst..dst + width]
+                .copy_from_slice(&block[y * width..(y + 1) * width]);
+        }
+        try!(self.texture.update_region(encoder, &block, [rect[0], rect[1]],
+                                        [slot[1], height as u32]));
+
+        self.tick += 1;
+        self.entries.insert(key, Entry {
+            rect: rect,
+            shelf: shelf,
+            slot: slot,
+            last_used: self.tick,
+            frame: self.frame,
+        });
+        Ok(Region::new(0, rect, self.size))
+    }
+
+    /// Removes all glyphs.
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+        self.entries.clear();
+    }
+
+    /// Returns the number of cached glyphs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no glyphs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn allocate(&mut self, size: [u32; 2]) -> Option<(usize, [u32; 2])> {
+        // Pick the shortest shelf that is tall enough.
+        let mut best: Option<usize> = None;
+        for (i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < size[1] { continue; }
+            let fits = shelf.x + size[0] <= self.size[0] ||
+                shelf.free.iter().any(|slot| slot[1] >= size[0]);
+            if !fits { continue; }
+            let better = match best {
+                None => true,
+                Some(j) => shelf.height < self.shelves[j].height,
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+
+        if let Some(i) = best {
+            let shelf = &mut self.shelves[i];
+            let free = shelf.free.iter().position(|slot| slot[1] >= size[0]);
+            let slot = match free {
+                Some(j) => shelf.free.swap_remove(j),
+                None => {
+                    let slot = [shelf.x, size[0]];
+                    shelf.x += size[0];
+                    slot
+                }
+            };
+            return Some((i, slot));
+        }
+
+        let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if size[0] > self.size[0] || y + size[1] > self.size[1] {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y: y,
+            height: size[1],
+            x: size[0],
+            free: vec![],
+        });
+        Some((self.shelves.len() - 1, [0, size[0]]))
+    }
+
+    /// Evicts the least recently used glyph not used in the current frame.
+    ///
+    /// Returns `false` if there is no such glyph.
+    fn evict(&mut self) -> bool {
+        let frame = self.frame;
+        let key = match self.entries.iter()
+            .filter(|&(_, entry)| entry.frame != frame)
+            .min_by_key(|&(_, entry)| entry.last_used)
+        {
+            Some((key, _)) => key.clone(),
+            None => return false,
+        };
+        if let Some(entry) = self.entries.remove(&key) {
+            self.shelves[entry.shelf].free.push(entry.slot);
+        }
+        true
+    }
+
+    fn grow<F>(&mut self, factory: &mut F) -> Result<(), Error>
+        where F: gfx::Factory<R>
+    {
+        let size = [cmp::min(self.size[0] * 2, self.max_size[0]),
+                    cmp::min(self.size[1] * 2, self.max_size[1])];
+        let mut pixels = vec![0; size[0] as usize * size[1] as usize];
+        for y in 0..self.size[1] as usize {
+            let src = y * self.size[0] as usize;
+            let dst = y * size[0] as usize;
+            pixels[dst..dst + self.size[0] as usize].copy_from_slice(
+                &self.pixels[src..src + self.size[0] as usize]);
+        }
+        self.texture = try!(create_texture(factory, &pixels, size,
+                                           &self.settings));
+        self.pixels = pixels;
+        self.size = size;
+        Ok(())
+    }
+}
+
+impl<R, K> ImageSize for GlyphCache<R, K> where R: gfx::Resources {
+    #[inline(always)]
+    fn get_size(&self) -> (u32, u32) {
+        (self.size[0], self.size[1])
+    }
+}
+
+fn create_texture<R, F>(
+    factory: &mut F,
+    pixels: &[u8],
+    size: [u32; 2],
+    settings: &TextureSettings
+) -> Result<Texture<R>, Error>
+    where R: gfx::Resources,
+          F: gfx::Factory<R>
+{
+    let mut texture = try!(Texture::create_with_format(factory,
+        PixelFormat::R8, pixels, size, Flip::None, Alpha::Straight,
+        Usage::Dynamic, settings));
+    let view = try!(factory.view_texture_as_shader_resource_raw(
+        &texture.surface, gfx::tex::ResourceDesc {
+            channel: PixelFormat::R8.channel_type(),
+            layer: None,
+            min: 0,
+            max: 0,
+            swizzle: Swizzle(ChannelSource::One, ChannelSource::One,
+                             ChannelSource::One, ChannelSource::X),
+        }));
+    texture.view = Typed::new(view);
+    Ok(texture)
+}
@@ -0,0 +1,341 @@
+//! Khronos KTX and KTX2 files.
+
+use std::cmp;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use gfx;
+use texture::TextureSettings;
+
+use compressed::ASTC_BLOCKS;
+use container::read_u32;
+use {CompressedFormat, Error, PixelFormat, Surface, SurfaceFormat, Texture};
+
+const KTX1_ID: [u8; 12] =
+    [0xab, 0x4b, 0x54, 0x58, 0x20, 0x31, 0x31, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a];
+const KTX2_ID: [u8; 12] =
+    [0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a];
+const KTX1_HEADER_LEN: usize = 64;
+const KTX2_HEADER_LEN: usize = 80;
+const KTX2_LEVEL_LEN: usize = 24;
+const ENDIANNESS: u32 = 0x0403_0201;
+
+impl Surface {
+    /// Reads a KTX or KTX2 file.
+    ///
+    /// Supercompressed KTX2 files and big endian KTX files
+    /// are not supported.
+    pub fn from_ktx(bytes: &[u8]) -> Result<Surface, Error> {
+        if bytes.starts_with(&KTX1_ID) {
+            read_ktx1(bytes)
+        } else if bytes.starts_with(&KTX2_ID) {
+            read_ktx2(bytes)
+        } else {
+            Err(Error::Malformed("not a KTX file"))
+        }
+    }
+}
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a texture from the bytes of a KTX or KTX2 file,
+    /// with all mipmap levels, array layers and cube faces.
+    pub fn from_ktx<F>(
+        factory: &mut F,
+        bytes: &[u8],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let surface = try!(Surface::from_ktx(bytes));
+        Texture::from_surface(factory, &surface, settings)
+    }
+
+    /// Creates a texture from a KTX or KTX2 file.
+    pub fn from_ktx_path<F, P>(
+        factory: &mut F,
+        path: P,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>,
+              P: AsRef<Path>
+    {
+        let mut bytes = vec![];
+        try!(try!(File::open(path)).read_to_end(&mut bytes));
+        Texture::from_ktx(factory, &bytes, settings)
+    }
+}
+
+fn read_ktx1(bytes: &[u8]) -> Result<Surface, Error> {
+    if bytes.len() < KTX1_HEADER_LEN {
+        return Err(Error::Malformed("KTX header is truncated"));
+    }
+    if read_u32(bytes, 12) != ENDIANNESS {
+        return Err(Error::Malformed("big endian KTX files are not supported"));
+    }
+    let format = try!(gl_format(read_u32(bytes, 28)));
+    let mut surface = try!(new_surface(format,
+        [read_u32(bytes, 36), read_u32(bytes, 40), read_u32(bytes, 44)],
+        read_u32(bytes, 48), read_u32(bytes, 52), read_u32(bytes, 56)));
+    let mut offset = KTX1_HEADER_LEN + read_u32(bytes, 60) as usize;
+    if bytes.len() < offset {
+        return Err(Error::BufferSize { expected: offset, found: bytes.len() });
+    }
+    let faces = try!(check_counts(&surface, bytes.len() - offset));
+
+    // Levels are stored first, with rows aligned to 4 bytes.
+    let levels = surface.levels as usize;
+    let mut slices = vec![vec![]; faces * levels];
+    for level in 0..surface.levels {
+        // Skip the image size, which is ambiguous for cube maps.
+        offset += 4;
+        let size = surface.level_size(level);
+        let (rows, row_len) = match surface.format {
+            SurfaceFormat::Pixel(format) =>
+                (size[1] as usize * size[2] as usize,
+                 size[0] as usize * format.bytes_per_pixel()),
+            SurfaceFormat::Compressed(_) =>
+                (1, try!(surface.level_len(level))),
+        };
+        let stride = align(row_len);
+        for face in 0..faces {
+            let slice = &mut slices[face * levels + level as usize];
+            for _ in 0..rows {
+                slice.extend_from_slice(try!(sub(bytes, offset, row_len)));
+                offset += stride;
+            }
+            offset = align(offset);
+        }
+    }
+    surface.data = slices;
+    Ok(surface)
+}
+
+fn read_ktx2(bytes: &[u8]) -> Result<Surface, Error> {
+    if bytes.len() < KTX2_HEADER_LEN {
+        return Err(Error::Malformed("KTX2 header is truncated"));
+    }
+    let scheme = read_u32(bytes, 44);
+    if scheme != 0 {
+        return Err(Error::Supercompressed(scheme));
+    }
+    let (format, bgr) = try!(vk_format(read_u32(bytes, 12)));
+    let mut surface = try!(new_surface(format,
+        [read_u32(bytes, 20), read_u32(bytes, 24), read_u32(bytes, 28)],
+        read_u32(bytes, 32), read_u32(bytes, 36), read_u32(bytes, 40)));
+
+    let faces = try!(check_counts(&surface, bytes.len() - KTX2_HEADER_LEN));
+
+    // Each level holds all layers and faces, tightly packed.
+    let levels = surface.levels as usize;
+    let mut slices = vec![vec![]; faces * levels];
+    for level in 0..surface.levels {
+        let index = KTX2_HEADER_LEN + level as usize * KTX2_LEVEL_LEN;
+        let entry = try!(sub(bytes, index, KTX2_LEVEL_LEN));
+        let (start, len) = (try!(read_len(entry, 0)), try!(read_len(entry, 8)));
+        let face_len = try!(surface.level_len(level));
+        if len != face_len * faces {
+            return Err(Error::BufferSize {
+                expected: face_len * faces,
+                found: len,
+            });
+        }
+        let data = try!(sub(bytes, start, len));
+        for (face, face_data) in data.chunks(face_len).enumerate() {
+            let slice = &mut slices[face * levels + level as usize];
+            slice.extend_from_slice(face_data);
+        }
+    }
+    if bgr {
+        for slice in &mut slices {
+            for pixel in slice.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+    }
+    surface.data = slices;
+    Ok(surface)
+}
+
+/// Creates a surface without data, treating zero sizes and counts
+/// as in KTX headers.
+fn new_surface(
+    format: SurfaceFormat,
+    size: [u32; 3],
+    layers: u32,
+    faces: u32,
+    levels: u32
+) -> Result<Surface, Error> {
+    let cube = match faces {
+        1 => false,
+        6 => true,
+        _ => return Err(Error::Malformed("number of faces is not 1 or 6")),
+    };
+    if size[0] == 0 {
+        return Err(Error::ZeroSize);
+    }
+    Ok(Surface {
+        format: format,
+        size: [size[0], cmp::max(size[1], 1), cmp::max(size[2], 1)],
+        layers: cmp::max(layers, 1),
+        cube: cube,
+        levels: cmp::max(levels, 1),
+        data: vec![],
+    })
+}
+
+/// Checks the levels, layers and faces of a header against the mipmap
+/// chain and the bytes left in the file, before anything is allocated.
+///
+/// Returns the number of faces of all layers.
+fn check_counts(surface: &Surface, remaining: usize) -> Result<usize, Error> {
+    try!(surface.check_levels());
+    let faces = try!(surface.face_count()) as usize;
+    let mut expected: usize = 0;
+    for level in 0..surface.levels {
+        let len = try!(surface.level_len(level));
+        expected = match len.checked_mul(faces)
+            .and_then(|len| len.checked_add(expected)) {
+            Some(expected) => expected,
+            None => return Err(Error::Malformed("size does not fit in memory")),
+        };
+    }
+    if remaining < expected {
+        return Err(Error::BufferSize {
+            expected: expected,
+            found: remaining,
+        });
+    }
+    Ok(faces)
+}
+
+/// Returns a part of the file, or an error if it is truncated.
+fn sub(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], Error> {
+    match offset.checked_add(len) {
+        Some(end) if end <= bytes.len() => Ok(&bytes[offset..end]),
+        _ => Err(Error::BufferSize {
+            expected: offset.saturating_add(len),
+            found: bytes.len(),
+        }),
+    }
+}
+
+/// Reads a little endian `u64` used as a byte offset or length.
+fn read_len(bytes: &[u8], offset: usize) -> Result<usize, Error> {
+    if read_u32(bytes, offset + 4) != 0 {
+        return Err(Error::Malformed("offset does not fit in memory"));
+    }
+    Ok(read_u32(bytes, offset) as usize)
+}
+
+fn align(value: usize) -> usize {
+    (value + 3) & !3
+}
+
+/// Maps a GL internal format of a KTX file.
+fn gl_format(format: u32) -> Result<SurfaceFormat, Error> {
+    let compressed = match format {
+        0x8058 => return Ok(SurfaceFormat::Pixel(PixelFormat::Rgba8)),
+        0x8c43 => return Ok(SurfaceFormat::Pixel(PixelFormat::Srgba8)),
+        0x8229 => return Ok(SurfaceFormat::Pixel(PixelFormat::R8)),
+        0x822b => return Ok(SurfaceFormat::Pixel(PixelFormat::Rg8)),
+        0x881a => return Ok(SurfaceFormat::Pixel(PixelFormat::Rgba16F)),
+        0x8814 => return Ok(SurfaceFormat::Pixel(PixelFormat::Rgba32F)),
+        0x83f0 | 0x83f1 | 0x8c4c | 0x8c4d => CompressedFormat::Bc1,
+        0x83f2 | 0x8c4e => CompressedFormat::Bc2,
+        0x83f3 | 0x8c4f => CompressedFormat::Bc3,
+        0x8dbb => CompressedFormat::Bc4,
+        0x8dbd => CompressedFormat::Bc5,
+        0x8e8c | 0x8e8d => CompressedFormat::Bc7,
+        0x9274 | 0x9275 => CompressedFormat::Etc2Rgb8,
+        0x9278 | 0x9279 => CompressedFormat::Etc2Rgba8,
+        0x93b0...0x93bd => astc((format - 0x93b0) as usize),
+        0x93d0...0x93dd => astc((format - 0x93d0) as usize),
+        _ => return Err(Error::UnsupportedFormat(format)),
+    };
+    Ok(SurfaceFormat::Compressed(compressed))
+}
+
+/// Maps a Vulkan format of a KTX2 file,
+/// returning whether red and blue are swapped.
+fn vk_format(format: u32) -> Result<(SurfaceFormat, bool), Error> {
+    let compressed = match format {
+        9 => return Ok((SurfaceFormat::Pixel(PixelFormat::R8), false)),
+        16 => return Ok((SurfaceFormat::Pixel(PixelFormat::Rg8), false)),
+        37 => return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba8), false)),
+        43 => return Ok((SurfaceFormat::Pixel(PixelFormat::Srgba8), false)),
+        44 => return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba8), true)),
+        50 => return Ok((SurfaceFormat::Pixel(PixelFormat::Srgba8), true)),
+        97 => return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba16F), false)),
+        109 => return Ok((SurfaceFormat::Pixel(PixelFormat::Rgba32F), false)),
+        131...134 => CompressedFormat::Bc1,
+        135 | 136 => CompressedFormat::Bc2,
+        137 | 138 => CompressedFormat::Bc3,
+        139 => CompressedFormat::Bc4,
+        141 => CompressedFormat::Bc5,
+        145 | 146 => CompressedFormat::Bc7,
+        147 | 148 => CompressedFormat::Etc2Rgb8,
+        151 | 152 => CompressedFormat::Etc2Rgba8,
+        157...184 => astc(((format - 157) / 2) as usize),
+        _ => return Err(Error::UnsupportedFormat(format)),
+    };
+    Ok((SurfaceFormat::Compressed(compressed), false))
+}
+
+fn astc(index: usize) -> CompressedFormat {
+    let (w, h) = ASTC_BLOCKS[index];
+    CompressedFormat::Astc(w, h)
+}
+
+#[cfg(test)]
+mod tests {
+    use {Error, PixelFormat, Surface, SurfaceFormat};
+
+    #[test]
+    fn ktx1_row_padding() {
+        let bytes = include_bytes!("../tests/fixtures/r8_mips.ktx");
+        let surface = Surface::from_ktx(bytes).unwrap();
+        assert_eq!(surface.format, SurfaceFormat::Pixel(PixelFormat::R8));
+        assert_eq!(surface.size, [3, 2, 1]);
+        assert_eq!(surface.levels, 2);
+        assert_eq!(&surface.data[0][..], &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(&surface.data[1][..], &[7]);
+    }
+
+    #[test]
+    fn ktx2_cube() {
+        let bytes = include_bytes!("../tests/fixtures/rgba8_cube.ktx2");
+        let surface = Surface::from_ktx(bytes).unwrap();
+        assert_eq!(surface.format, SurfaceFormat::Pixel(PixelFormat::Srgba8));
+        assert!(surface.cube);
+        assert_eq!(surface.data.len(), 6);
+        assert_eq!(&surface.data[3][..], &[3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = include_bytes!("../tests/fixtures/rgba8_cube.ktx2");
+        match Surface::from_ktx(&bytes[..bytes.len() - 1]) {
+            Err(Error::BufferSize { .. }) => {}
+            _ => panic!("expected a buffer size error"),
+        }
+    }
+
+    #[test]
+    fn corrupt_header() {
+        // A KTX2 header with 0xffffffff levels and no data.
+        let bytes = include_bytes!("../tests/fixtures/corrupt_levels.ktx2");
+        match Surface::from_ktx(bytes) {
+            Err(Error::Levels(0xffff_ffff)) => {}
+            _ => panic!("expected a levels error"),
+        }
+        // A cube map array with 0xffffffff layers.
+        let fixture = include_bytes!("../tests/fixtures/r8_mips.ktx");
+        let mut bytes = fixture.to_vec();
+        for byte in &mut bytes[48..52] { *byte = 0xff; }
+        bytes[52] = 6;
+        match Surface::from_ktx(&bytes) {
+            Err(Error::Malformed(_)) => {}
+            _ => panic!("expected a malformed file error"),
+        }
+    }
+}
@@ -3,85 +3,307 @@
 //! A Gfx texture representation that works nicely with Piston libraries.
 
 extern crate gfx;
+extern crate gfx_core;
 extern crate texture;
 extern crate image;
 
 pub use texture::*;
+pub use alpha::Alpha;
+pub use compressed::{check_compressed_levels, CompressedFormat};
+pub use container::{Surface, SurfaceFormat};
+pub use cube::CubeLayout;
+pub use error::Error;
+pub use flip::Flip;
+pub use render_target::{MultisampledTarget, RenderTexture};
+pub use streaming::StreamingTexture;
+pub use usage::Usage;
+pub use format::{ColorSpace, PixelFormat};
 
+use std::cmp;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::u16;
 use std::path::Path;
 use image::{
+    imageops,
     DynamicImage,
+    FilterType,
     GenericImage,
+    ImageBuffer,
+    ImageFormat,
     RgbaImage,
 };
 use gfx::traits::*;
-use gfx::CombinedError;
-use gfx::format::{Srgba8, R8_G8_B8_A8};
-
-/// Flip settings.
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub enum Flip {
-    /// Does not flip.
-    None,
-    /// Flips image vertically.
-    Vertical,
-}
+use gfx::format::{Float, R8, R8_G8, R16_G16_B16_A16, R32_G32_B32_A32,
+                  Rgba8, Srgba8, Unorm};
+use gfx::tex::{FilterMethod, SamplerInfo, WrapMode};
+use gfx_core::factory::Typed;
+
+pub mod alpha;
+pub mod atlas;
+pub mod glyph_cache;
+
+mod array;
+mod compressed;
+mod container;
+mod dds;
+mod ktx;
+mod cube;
+mod volume;
+mod error;
+mod flip;
+mod format;
+mod packer;
+mod render_target;
+mod streaming;
+mod usage;
 
 /// Represents a texture.
 pub struct Texture<R> where R: gfx::Resources {
     /// Pixel storage for texture.
-    pub surface: gfx::handle::Texture<R, R8_G8_B8_A8>,
+    pub surface: gfx::handle::RawTexture<R>,
+    /// Storage format of the pixels.
+    pub format: PixelFormat,
+    /// Whether color channels are premultiplied by alpha.
+    pub alpha: Alpha,
+    /// How the texture is updated after creation.
+    pub usage: Usage,
+    /// Sampler for texture.
+    pub sampler: gfx::handle::Sampler<R>,
     /// View used by shader.
     pub view: gfx::handle::ShaderResourceView<R, [f32; 4]>
 }
 
 impl<R: gfx::Resources> Texture<R> {
     /// Returns empty texture.
-    pub fn empty<F>(factory: &mut F) -> Result<Self, CombinedError>
+    pub fn empty<F>(factory: &mut F) -> Result<Self, Error>
         where F: gfx::Factory<R>
     {
         CreateTexture::create(factory, Format::Rgba8, &[0u8; 4], [1, 1],
                               &TextureSettings::new())
     }
 
+    /// Creates a zeroed texture that is updated frequently from the CPU,
+    /// such as video frames or software rendered canvases.
+    ///
+    /// The texture has a single level, whatever the settings say.
+    pub fn new_dynamic<F>(
+        factory: &mut F,
+        format: PixelFormat,
+        size: [u32; 2],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        try!(texture_size(size));
+        let len = match (size[0] as usize).checked_mul(size[1] as usize)
+            .and_then(|len| len.checked_mul(format.bytes_per_pixel())) {
+            Some(len) => len,
+            None => return Err(Error::DimensionOverflow(
+                cmp::max(size[0], size[1]))),
+        };
+        Texture::create_with_format(factory, format, &vec![0; len], size,
+                                    Flip::None, Alpha::Straight,
+                                    Usage::Dynamic, settings)
+    }
+
     /// Creates a texture from path.
+    ///
+    /// The texture is `Dynamic`, or `Immutable` when mipmaps are
+    /// generated.
     pub fn from_path<F, P>(
         factory: &mut F,
         path: P,
         flip: Flip,
         settings: &TextureSettings,
-    ) -> Result<Self, String>
+    ) -> Result<Self, Error>
         where F: gfx::Factory<R>,
               P: AsRef<Path>
     {
-        let img = try!(image::open(path).map_err(|e| e.to_string()));
+        Texture::from_path_with_color_space(factory, path, flip,
+            ColorSpace::Srgb, Alpha::Straight, default_usage(settings),
+            settings)
+    }
 
-        let img = match img {
-            DynamicImage::ImageRgba8(img) => img,
-            img => img.to_rgba()
-        };
+    /// Creates a texture from path in the given color space.
+    ///
+    /// The color channels are multiplied by alpha when `alpha` is
+    /// `Alpha::Premultiplied`.
+    pub fn from_path_with_color_space<F, P>(
+        factory: &mut F,
+        path: P,
+        flip: Flip,
+        color_space: ColorSpace,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings,
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>,
+              P: AsRef<Path>
+    {
+        let img = try!(image::open(path));
+        Texture::from_dynamic_image(factory, img, flip, color_space, alpha,
+                                    usage, settings)
+    }
 
-        let img = if flip == Flip::Vertical {
-            image::imageops::flip_vertical(&img)
-        } else {
-            img
-        };
+    /// Creates a texture from encoded image bytes.
+    ///
+    /// The image format is guessed from the bytes when not given.
+    /// The texture is `Dynamic`, or `Immutable` when mipmaps are
+    /// generated.
+    pub fn from_bytes<F>(
+        factory: &mut F,
+        bytes: &[u8],
+        format: Option<ImageFormat>,
+        flip: Flip,
+        settings: &TextureSettings,
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let img = try!(match format {
+            Some(format) => image::load_from_memory_with_format(bytes, format),
+            None => image::load_from_memory(bytes),
+        });
+        Texture::from_dynamic_image(factory, img, flip,
+            ColorSpace::Srgb, Alpha::Straight, default_usage(settings),
+            settings)
+    }
 
-        Texture::from_image(factory, &img, settings).map_err(
-            |e| format!("{:?}", e))
+    /// Creates a texture from a reader of encoded image data.
+    ///
+    /// The image format is guessed from the first bytes when not given.
+    /// The texture is `Dynamic`, or `Immutable` when mipmaps are
+    /// generated.
+    pub fn from_reader<F, Rd>(
+        factory: &mut F,
+        mut reader: Rd,
+        format: Option<ImageFormat>,
+        flip: Flip,
+        settings: &TextureSettings,
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>,
+              Rd: Read + Seek
+    {
+        let format = match format {
+            Some(format) => format,
+            None => {
+                let start = try!(reader.seek(SeekFrom::Current(0)));
+                let mut header = vec![];
+                try!((&mut reader).take(16).read_to_end(&mut header));
+                try!(reader.seek(SeekFrom::Start(start)));
+                try!(image::guess_format(&header))
+            }
+        };
+        let img = try!(image::load(BufReader::new(reader), format));
+        Texture::from_dynamic_image(factory, img, flip,
+            ColorSpace::Srgb, Alpha::Straight, default_usage(settings),
+            settings)
     }
 
     /// Creates a texture from image.
+    ///
+    /// Generates mipmaps with a triangle filter when enabled in settings.
+    /// The texture is `Dynamic`, or `Immutable` when mipmaps are
+    /// generated.
     pub fn from_image<F>(
         factory: &mut F,
         img: &RgbaImage,
+        flip: Flip,
         settings: &TextureSettings
-    ) -> Result<Self, CombinedError>
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        Texture::from_image_with_color_space(factory, img, flip,
+            ColorSpace::Srgb, Alpha::Straight, default_usage(settings),
+            settings)
+    }
+
+    /// Creates a texture from image in the given color space.
+    ///
+    /// The color channels are multiplied by alpha when `alpha` is
+    /// `Alpha::Premultiplied`.
+    /// Generates mipmaps with a triangle filter when enabled in settings,
+    /// unless `usage` is `Usage::Dynamic`.
+    pub fn from_image_with_color_space<F>(
+        factory: &mut F,
+        img: &RgbaImage,
+        flip: Flip,
+        color_space: ColorSpace,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
         where F: gfx::Factory<R>
     {
         let (width, height) = img.dimensions();
-        CreateTexture::create(factory, Format::Rgba8,
-                              img, [width, height], settings)
+        let format = PixelFormat::Rgba8.with_color_space(color_space);
+        Texture::create_with_format(factory, format, img, [width, height],
+                                    flip, alpha, usage, settings)
+    }
+
+    /// Creates a texture from image with a full mipmap chain,
+    /// downsampling each level with the given filter.
+    ///
+    /// Like all mipmapped textures, the texture is `Immutable`.
+    pub fn from_image_with_mipmaps<F>(
+        factory: &mut F,
+        img: &RgbaImage,
+        flip: Flip,
+        filter: FilterType,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        if flip == Flip::None {
+            Texture::create_mipmapped(factory, PixelFormat::Srgba8,
+                img, filter, Alpha::Straight, Usage::Immutable, settings)
+        } else {
+            Texture::create_mipmapped(factory, PixelFormat::Srgba8,
+                &flip.apply(img), filter, Alpha::Straight, Usage::Immutable,
+                settings)
+        }
+    }
+
+    /// Creates a texture from memory stored in the given pixel format.
+    ///
+    /// Flipping reorders the memory before upload.
+    /// Premultiplied alpha and mipmaps with a triangle filter are only
+    /// supported for formats with 8 bit RGBA channels.
+    /// `Usage::Dynamic` textures have a single level, since updates
+    /// only reach the first level, so mipmaps are not generated for them.
+    pub fn create_with_format<F>(
+        factory: &mut F,
+        format: PixelFormat,
+        memory: &[u8],
+        size: [u32; 2],
+        flip: Flip,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        try!(texture_size(size));
+        try!(check_buffer(format, memory, size));
+        let mut memory =
+            flip.apply_memory(memory, size, format.bytes_per_pixel());
+        let size = flip.size(size);
+
+        let rgba8 = format == PixelFormat::Rgba8 ||
+                    format == PixelFormat::Srgba8;
+        let alpha = if rgba8 { alpha } else { Alpha::Straight };
+        if alpha == Alpha::Premultiplied {
+            alpha::premultiply(memory.to_mut());
+        }
+        if rgba8 && settings.get_generate_mipmap() && usage != Usage::Dynamic {
+            let img: RgbaImage =
+                ImageBuffer::from_raw(size[0], size[1], memory.into_owned())
+                    .expect("memory was checked against the size");
+            return Texture::create_mipmapped(factory, format, &img,
+                FilterType::Triangle, alpha, usage, settings);
+        }
+
+        Texture::create_levels(factory, format, &[&*memory], size,
+                               alpha, usage, settings)
     }
 
     /// Creates texture from memory alpha.
@@ -90,16 +312,20 @@ impl<R: gfx::Resources> Texture<R> {
         buffer: &[u8],
         width: u32,
         height: u32,
+        flip: Flip,
         settings: &TextureSettings
-    ) -> Result<Self, CombinedError>
+    ) -> Result<Self, Error>
         where F: gfx::Factory<R>
     {
         if width == 0 || height == 0 {
-            return Texture::empty(factory);
+            return Err(Error::ZeroSize);
         }
 
         let size = [width, height];
-        let buffer = texture::ops::alpha_to_rgba8(buffer, size);
+        try!(check_buffer(PixelFormat::R8, buffer, size));
+        let buffer = flip.apply_memory(buffer, size, 1);
+        let size = flip.size(size);
+        let buffer = texture::ops::alpha_to_rgba8(&buffer, size);
         CreateTexture::create(factory, Format::Rgba8, &buffer, size, settings)
     }
 
@@ -108,12 +334,315 @@ impl<R: gfx::Resources> Texture<R> {
         &mut self,
         encoder: &mut gfx::Encoder<R, C>,
         img: &RgbaImage
-    ) -> Result<(), gfx::UpdateError<[u16; 3]>>
+    ) -> Result<(), Error>
         where C: gfx::CommandBuffer<R>
     {
         let (width, height) = img.dimensions();
         UpdateTexture::update(self, encoder, Format::Rgba8,
-                              img, [width, height])
+                              img, [0, 0], [width, height])
+    }
+
+    /// Updates a rectangle of the texture with memory stored in the
+    /// pixel format of the texture.
+    ///
+    /// Straight alpha memory is premultiplied when the texture
+    /// stores premultiplied alpha.
+    /// Returns `Error::OutOfBounds` when the rectangle does not lie
+    /// inside the texture.
+    pub fn update_region<C>(
+        &mut self,
+        encoder: &mut gfx::Encoder<R, C>,
+        memory: &[u8],
+        offset: [u32; 2],
+        size: [u32; 2]
+    ) -> Result<(), Error>
+        where C: gfx::CommandBuffer<R>
+    {
+        let (width, height) = try!(texture_size(size));
+        let (w, h) = self.get_size();
+        try!(check_region([offset[0], offset[1], 0], [size[0], size[1], 1],
+                          [w, h, 1]));
+        try!(check_buffer(self.format, memory, size));
+        let mut img = self.surface.get_info().to_image_info(0);
+        img.xoffset = try!(dimension(offset[0]));
+        img.yoffset = try!(dimension(offset[1]));
+        img.width = width;
+        img.height = height;
+        self.update_image(encoder, img, memory)
+    }
+}
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Uploads memory that has been validated against the image info.
+    fn update_image<C>(
+        &mut self,
+        encoder: &mut gfx::Encoder<R, C>,
+        img: gfx::tex::NewImageInfo,
+        memory: &[u8]
+    ) -> Result<(), Error>
+        where C: gfx::CommandBuffer<R>
+    {
+        if self.usage != Usage::Dynamic {
+            return Err(Error::NotDynamic(self.usage));
+        }
+        let premultiplied;
+        let memory = if self.alpha == Alpha::Premultiplied {
+            let mut buf = memory.to_vec();
+            alpha::premultiply(&mut buf);
+            premultiplied = buf;
+            &premultiplied[..]
+        } else {
+            memory
+        };
+        let res = match self.format {
+            PixelFormat::Rgba8 => update_texture::<_, _, Rgba8>(
+                encoder, &self.surface, img, memory),
+            PixelFormat::Srgba8 => update_texture::<_, _, Srgba8>(
+                encoder, &self.surface, img, memory),
+            PixelFormat::R8 => update_texture::<_, _, (R8, Unorm)>(
+                encoder, &self.surface, img, memory),
+            PixelFormat::Rg8 => update_texture::<_, _, (R8_G8, Unorm)>(
+                encoder, &self.surface, img, memory),
+            PixelFormat::Rgba16F =>
+                update_texture::<_, _, (R16_G16_B16_A16, Float)>(
+                    encoder, &self.surface, img, memory),
+            PixelFormat::Rgba32F =>
+                update_texture::<_, _, (R32_G32_B32_A32, Float)>(
+                    encoder, &self.surface, img, memory),
+        };
+        res.map_err(Error::from)
+    }
+
+    fn from_dynamic_image<F>(
+        factory: &mut F,
+        img: DynamicImage,
+        flip: Flip,
+        color_space: ColorSpace,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings,
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let img = match img {
+            DynamicImage::ImageRgba8(img) => img,
+            img => img.to_rgba()
+        };
+
+        Texture::from_image_with_color_space(factory, &img, flip,
+            color_space, alpha, usage, settings)
+    }
+
+    fn create_mipmapped<F>(
+        factory: &mut F,
+        format: PixelFormat,
+        img: &RgbaImage,
+        filter: FilterType,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = img.dimensions();
+        let chain = mipmap_chain(img, filter);
+        let mut levels: Vec<&[u8]> = vec![&**img];
+        levels.extend(chain.iter().map(|level| &**level));
+        Texture::create_levels(factory, format, &levels,
+                               [width, height], alpha, usage, settings)
+    }
+
+    fn create_levels<F>(
+        factory: &mut F,
+        format: PixelFormat,
+        levels: &[&[u8]],
+        size: [u32; 2],
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = try!(texture_size(size));
+        for (i, level) in levels.iter().enumerate() {
+            let level_size = [cmp::max(size[0] >> i, 1),
+                              cmp::max(size[1] >> i, 1)];
+            try!(check_buffer(format, level, level_size));
+        }
+        let kind = gfx::tex::Kind::D2(width, height, gfx::tex::AaMode::Single);
+        Texture::create_surface(factory, kind, format, levels,
+                                levels.len() as gfx::tex::Level,
+                                alpha, usage, settings)
+    }
+
+    /// Creates a texture of any kind from memory that has been validated.
+    ///
+    /// `data` holds one slice per level of each face of each layer,
+    /// in that order.
+    fn create_surface<F>(
+        factory: &mut F,
+        kind: gfx::tex::Kind,
+        format: PixelFormat,
+        data: &[&[u8]],
+        num_levels: gfx::tex::Level,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        Texture::create_bound(factory, kind, format, gfx::SHADER_RESOURCE,
+                              Some(data), num_levels, alpha, usage, settings)
+    }
+
+    /// Creates a texture with the given bind flags,
+    /// leaving it uninitialized when there is no data.
+    fn create_bound<F>(
+        factory: &mut F,
+        kind: gfx::tex::Kind,
+        format: PixelFormat,
+        bind: gfx::Bind,
+        data: Option<&[&[u8]]>,
+        num_levels: gfx::tex::Level,
+        alpha: Alpha,
+        usage: Usage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let desc = gfx::tex::Descriptor {
+            kind: kind,
+            levels: num_levels,
+            format: format.surface_type(),
+            bind: bind | gfx::SHADER_RESOURCE,
+            usage: usage.gfx_usage(),
+        };
+        let channel = format.channel_type();
+        let surface = try!(factory.create_texture_raw(
+            desc, Some(channel), data));
+        let view = try!(factory.view_texture_as_shader_resource_raw(
+            &surface, gfx::tex::ResourceDesc {
+                channel: channel,
+                layer: None,
+                min: 0,
+                max: num_levels - 1,
+                swizzle: gfx::format::Swizzle::new(),
+            }));
+        let sampler = factory.create_sampler(sampler_info(settings,
+                                                          num_levels));
+        Ok(Texture {
+            surface: surface,
+            format: format,
+            alpha: alpha,
+            usage: usage,
+            sampler: sampler,
+            view: Typed::new(view),
+        })
+    }
+}
+
+/// Returns the usage of textures created without an explicit usage.
+///
+/// Mipmapped textures are immutable, since updates only reach one level.
+fn default_usage(settings: &TextureSettings) -> Usage {
+    if settings.get_generate_mipmap() {
+        Usage::Immutable
+    } else {
+        Usage::Dynamic
+    }
+}
+
+fn dimension(value: u32) -> Result<u16, Error> {
+    if value > u16::MAX as u32 {
+        Err(Error::DimensionOverflow(value))
+    } else {
+        Ok(value as u16)
+    }
+}
+
+fn texture_size(size: [u32; 2]) -> Result<(u16, u16), Error> {
+    if size[0] == 0 || size[1] == 0 {
+        return Err(Error::ZeroSize);
+    }
+    Ok((try!(dimension(size[0])), try!(dimension(size[1]))))
+}
+
+/// Checks that a region with the given offset and size lies inside
+/// a texture of the given size.
+fn check_region(
+    offset: [u32; 3],
+    size: [u32; 3],
+    bounds: [u32; 3]
+) -> Result<(), Error> {
+    for i in 0..3 {
+        match offset[i].checked_add(size[i]) {
+            Some(end) if end <= bounds[i] => {}
+            _ => return Err(Error::OutOfBounds { offset: offset, size: size }),
+        }
+    }
+    Ok(())
+}
+
+fn check_buffer(
+    format: PixelFormat,
+    memory: &[u8],
+    size: [u32; 2]
+) -> Result<(), Error> {
+    let expected = (size[0] as usize).checked_mul(size[1] as usize)
+        .and_then(|len| len.checked_mul(format.bytes_per_pixel()));
+    match expected {
+        Some(expected) if memory.len() == expected => Ok(()),
+        Some(expected) =>
+            Err(Error::BufferSize { expected: expected, found: memory.len() }),
+        None => Err(Error::DimensionOverflow(cmp::max(size[0], size[1]))),
+    }
+}
+
+/// Downsamples an image into successive mipmap levels.
+///
+/// The returned levels do not include the image itself.
+pub fn mipmap_chain(img: &RgbaImage, filter: FilterType) -> Vec<RgbaImage> {
+    let mut levels: Vec<RgbaImage> = vec![];
+    let (mut width, mut height) = img.dimensions();
+    while width > 1 || height > 1 {
+        width = cmp::max(width / 2, 1);
+        height = cmp::max(height / 2, 1);
+        let level = match levels.last() {
+            Some(prev) => imageops::resize(prev, width, height, filter),
+            None => imageops::resize(img, width, height, filter),
+        };
+        levels.push(level);
+    }
+    levels
+}
+
+/// Converts texture settings into sampler info for a texture
+/// with the given number of mipmap levels.
+///
+/// Mipmaps are only sampled when there is more than one level.
+pub fn sampler_info(
+    settings: &TextureSettings,
+    levels: gfx::tex::Level
+) -> SamplerInfo {
+    let filter = match (settings.get_min(), settings.get_mag(),
+                        levels > 1, settings.get_mipmap()) {
+        (Filter::Nearest, Filter::Nearest, false, _) => FilterMethod::Scale,
+        (Filter::Nearest, Filter::Nearest, true, _) => FilterMethod::Mipmap,
+        (_, _, true, Filter::Linear) => FilterMethod::Trilinear,
+        _ => FilterMethod::Bilinear,
+    };
+    let mut info = SamplerInfo::new(filter, wrap_mode(settings.get_wrap_u()));
+    info.wrap_mode.1 = wrap_mode(settings.get_wrap_v());
+    info.border = settings.get_border_color().into();
+    info
+}
+
+fn wrap_mode(wrap: Wrap) -> WrapMode {
+    match wrap {
+        Wrap::ClampToEdge => WrapMode::Clamp,
+        Wrap::ClampToBorder => WrapMode::Border,
+        Wrap::Repeat => WrapMode::Tile,
+        Wrap::MirroredRepeat => WrapMode::Mirror,
     }
 }
 
@@ -121,23 +650,18 @@ impl<F, R> CreateTexture<F> for Texture<R>
     where F: gfx::Factory<R>,
           R: gfx::Resources
 {
-    type Error = CombinedError;
+    type Error = Error;
 
     fn create<S: Into<[u32; 2]>>(
         factory: &mut F,
-        _format: Format,
+        format: Format,
         memory: &[u8],
         size: S,
-        _settings: &TextureSettings
+        settings: &TextureSettings
     ) -> Result<Self, Self::Error> {
-        let size = size.into();
-        let (width, height) = (size[0] as u16, size[1] as u16);
-        let tex_kind = gfx::tex::Kind::D2(width, height,
-            gfx::tex::AaMode::Single);
-
-        let (surface, view) = try!(factory.create_texture_const_u8::<Srgba8>(
-            tex_kind, &[memory]));
-        Ok(Texture { surface: surface, view: view })
+        Texture::create_with_format(factory, format.into(), memory,
+            size.into(), Flip::None, Alpha::Straight, default_usage(settings),
+            settings)
     }
 }
 
@@ -145,24 +669,45 @@ impl<R, C> UpdateTexture<gfx::Encoder<R, C>> for Texture<R>
     where R: gfx::Resources,
           C: gfx::CommandBuffer<R>
 {
-    type Error = gfx::UpdateError<[u16; 3]>;
+    type Error = Error;
 
-    fn update<S: Into<[u32; 2]>>(
+    fn update<O, S>(
         &mut self,
         encoder: &mut gfx::Encoder<R, C>,
         _format: Format,
         memory: &[u8],
-        _size: S,
-    ) -> Result<(), Self::Error> {
-        encoder.update_texture::<_, Srgba8>(
-            &self.surface,
-            None,
-            self.surface.get_info().to_image_info(0),
-            gfx::cast_slice(memory),
-        ).map_err(|err| err.into())
+        offset: O,
+        size: S,
+    ) -> Result<(), Self::Error>
+        where O: Into<[u32; 2]>,
+              S: Into<[u32; 2]>
+    {
+        Texture::update_region(self, encoder, memory,
+                               offset.into(), size.into())
     }
 }
 
+fn update_texture<R, C, T>(
+    encoder: &mut gfx::Encoder<R, C>,
+    surface: &gfx::handle::RawTexture<R>,
+    img: gfx::tex::NewImageInfo,
+    memory: &[u8]
+) -> Result<(), gfx::UpdateError<[u16; 3]>>
+    where R: gfx::Resources,
+          C: gfx::CommandBuffer<R>,
+          T: gfx::format::TextureFormat,
+          <T::Surface as gfx::format::SurfaceTyped>::DataType: Copy
+{
+    let surface: gfx::handle::Texture<R, T::Surface> =
+        Typed::new(surface.clone());
+    encoder.update_texture::<_, T>(
+        &surface,
+        None,
+        img,
+        gfx::cast_slice(memory),
+    ).map_err(|err| err.into())
+}
+
 impl<R> ImageSize for Texture<R> where R: gfx::Resources {
     #[inline(always)]
     fn get_size(&self) -> (u32, u32) {
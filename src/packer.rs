@@ -0,0 +1,142 @@
+//! Skyline rectangle packing.
+
+use std::cmp;
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Packs rectangles into an area using the skyline bottom-left heuristic.
+#[derive(Clone, Debug)]
+pub struct Packer {
+    size: [u32; 2],
+    skyline: Vec<Segment>,
+}
+
+impl Packer {
+    /// Creates a new packer for an area of the given size.
+    pub fn new(size: [u32; 2]) -> Packer {
+        Packer {
+            size: size,
+            skyline: vec![Segment { x: 0, y: 0, width: size[0] }],
+        }
+    }
+
+    /// Finds room for a rectangle and returns its position.
+    pub fn pack(&mut self, size: [u32; 2]) -> Option<[u32; 2]> {
+        let mut best: Option<(usize, u32, u32)> = None;
+        for i in 0..self.skyline.len() {
+            if let Some(y) = self.fit(i, size) {
+                let x = self.skyline[i].x;
+                let better = match best {
+                    None => true,
+                    Some((_, best_x, best_y)) =>
+                        y < best_y || (y == best_y && x < best_x),
+                };
+                if better {
+                    best = Some((i, x, y));
+                }
+            }
+        }
+
+        best.map(|(i, x, y)| {
+            self.insert(i, Segment { x: x, y: y + size[1], width: size[0] });
+            [x, y]
+        })
+    }
+
+    fn fit(&self, i: usize, size: [u32; 2]) -> Option<u32> {
+        let x = self.skyline[i].x;
+        if x + size[0] > self.size[0] {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut covered = 0;
+        for segment in &self.skyline[i..] {
+            if covered >= size[0] { break; }
+            y = cmp::max(y, segment.y);
+            if y + size[1] > self.size[1] {
+                return None;
+            }
+            covered += segment.width;
+        }
+        Some(y)
+    }
+
+    fn insert(&mut self, i: usize, segment: Segment) {
+        self.skyline.insert(i, segment);
+
+        // Shrink or remove segments covered by the new one.
+        let end = segment.x + segment.width;
+        while i + 1 < self.skyline.len() {
+            let next = self.skyline[i + 1];
+            if next.x >= end { break; }
+            let overlap = end - next.x;
+            if next.width <= overlap {
+                self.skyline.remove(i + 1);
+            } else {
+                self.skyline[i + 1].x += overlap;
+                self.skyline[i + 1].width -= overlap;
+                break;
+            }
+        }
+
+        // Merge neighbours at the same height.
+        let mut j = 0;
+        while j + 1 < self.skyline.len() {
+            if self.skyline[j].y == self.skyline[j + 1].y {
+                self.skyline[j].width += self.skyline[j + 1].width;
+                self.skyline.remove(j + 1);
+            } else {
+                j += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Packer;
+
+    #[test]
+    fn bottom_left() {
+        let mut packer = Packer::new([4, 4]);
+        assert_eq!(packer.pack([2, 2]), Some([0, 0]));
+        assert_eq!(packer.pack([2, 2]), Some([2, 0]));
+        assert_eq!(packer.pack([4, 2]), Some([0, 2]));
+        assert_eq!(packer.pack([1, 1]), None);
+    }
+
+    #[test]
+    fn too_large() {
+        let mut packer = Packer::new([4, 4]);
+        assert_eq!(packer.pack([5, 1]), None);
+        assert_eq!(packer.pack([1, 5]), None);
+        assert_eq!(packer.pack([4, 4]), Some([0, 0]));
+    }
+
+    #[test]
+    fn no_overlap() {
+        let mut packer = Packer::new([64, 64]);
+        let mut rects: Vec<[u32; 4]> = vec![];
+        for i in 0..40 {
+            let size = [1 + i * 7 % 13, 1 + i * 5 % 11];
+            if let Some(pos) = packer.pack(size) {
+                let rect = [pos[0], pos[1], size[0], size[1]];
+                assert!(rect[0] + rect[2] <= 64 && rect[1] + rect[3] <= 64);
+                for other in &rects {
+                    assert!(rect[0] >= other[0] + other[2] ||
+                            other[0] >= rect[0] + rect[2] ||
+                            rect[1] >= other[1] + other[3] ||
+                            other[1] >= rect[1] + rect[3]);
+                }
+                rects.push(rect);
+            }
+        }
+        assert!(rects.len() > 20);
+    }
+}
@@ -0,0 +1,179 @@
+//! Textures that can be rendered to.
+
+use gfx;
+use gfx::format::{ChannelType, DepthStencil, Srgba8, SurfaceType};
+use gfx_core::factory::Typed;
+use texture::{ImageSize, TextureSettings};
+
+use texture_size;
+use {Alpha, Error, PixelFormat, Texture, Usage};
+
+/// A texture that can be rendered to and then drawn like any other texture.
+pub struct RenderTexture<R> where R: gfx::Resources {
+    /// Texture holding the rendered pixels.
+    pub texture: Texture<R>,
+    /// View used to render into the texture.
+    pub target: gfx::handle::RenderTargetView<R, Srgba8>,
+    /// View used for depth and stencil testing, if any.
+    pub depth: Option<gfx::handle::DepthStencilView<R, DepthStencil>>,
+}
+
+impl<R: gfx::Resources> RenderTexture<R> {
+    /// Creates a new render texture, optionally with a depth stencil target
+    /// of the same size.
+    pub fn new<F>(
+        factory: &mut F,
+        size: [u32; 2],
+        depth: bool,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = try!(texture_size(size));
+        let kind = gfx::tex::Kind::D2(width, height, gfx::tex::AaMode::Single);
+        let texture = try!(Texture::create_bound(factory, kind,
+            PixelFormat::Srgba8, gfx::RENDER_TARGET, None, 1,
+            Alpha::Straight, Usage::GpuOnly, settings));
+        let target = try!(create_target(factory, &texture.surface));
+        let depth = if depth {
+            Some(try!(create_depth(factory, kind)))
+        } else {
+            None
+        };
+        Ok(RenderTexture {
+            texture: texture,
+            target: target,
+            depth: depth,
+        })
+    }
+}
+
+impl<R> ImageSize for RenderTexture<R> where R: gfx::Resources {
+    #[inline(always)]
+    fn get_size(&self) -> (u32, u32) {
+        self.texture.get_size()
+    }
+}
+
+/// A multisampled surface that can be rendered to.
+///
+/// Multisampled surfaces can not be sampled like other textures,
+/// and Gfx has no command to resolve or blit them into one,
+/// so this is not a `Texture` and can only be read through `view`
+/// by shaders that fetch individual samples.
+pub struct MultisampledTarget<R> where R: gfx::Resources {
+    /// Pixel storage for the samples.
+    pub surface: gfx::handle::RawTexture<R>,
+    /// Multisampled view used by shaders that fetch individual samples.
+    pub view: gfx::handle::ShaderResourceView<R, [f32; 4]>,
+    /// View used to render into the surface.
+    pub target: gfx::handle::RenderTargetView<R, Srgba8>,
+    /// View used for depth and stencil testing, if any.
+    pub depth: Option<gfx::handle::DepthStencilView<R, DepthStencil>>,
+}
+
+impl<R: gfx::Resources> MultisampledTarget<R> {
+    /// Creates a new multisampled surface with the given number of
+    /// samples per pixel, optionally with a multisampled depth stencil target.
+    pub fn new<F>(
+        factory: &mut F,
+        size: [u32; 2],
+        samples: gfx::tex::NumSamples,
+        depth: bool
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = try!(texture_size(size));
+        let aa = if samples > 1 {
+            gfx::tex::AaMode::Multi(samples)
+        } else {
+            gfx::tex::AaMode::Single
+        };
+        let kind = gfx::tex::Kind::D2(width, height, aa);
+        let desc = gfx::tex::Descriptor {
+            kind: kind,
+            levels: 1,
+            format: PixelFormat::Srgba8.surface_type(),
+            bind: gfx::RENDER_TARGET | gfx::SHADER_RESOURCE,
+            usage: Usage::GpuOnly.gfx_usage(),
+        };
+        let channel = PixelFormat::Srgba8.channel_type();
+        let surface = try!(factory.create_texture_raw(
+            desc, Some(channel), None));
+        let view = try!(factory.view_texture_as_shader_resource_raw(
+            &surface, gfx::tex::ResourceDesc {
+                channel: channel,
+                layer: None,
+                min: 0,
+                max: 0,
+                swizzle: gfx::format::Swizzle::new(),
+            }));
+        let target = try!(create_target(factory, &surface));
+        let depth = if depth {
+            Some(try!(create_depth(factory, kind)))
+        } else {
+            None
+        };
+        Ok(MultisampledTarget {
+            surface: surface,
+            view: Typed::new(view),
+            target: target,
+            depth: depth,
+        })
+    }
+
+    /// Returns the number of samples per pixel.
+    pub fn get_samples(&self) -> gfx::tex::NumSamples {
+        let (_, _, _, aa) = self.surface.get_info().kind.get_dimensions();
+        aa.get_num_fragments()
+    }
+}
+
+impl<R> ImageSize for MultisampledTarget<R> where R: gfx::Resources {
+    #[inline(always)]
+    fn get_size(&self) -> (u32, u32) {
+        let (w, h, _, _) = self.surface.get_info().kind.get_dimensions();
+        (w as u32, h as u32)
+    }
+}
+
+fn create_target<R, F>(
+    factory: &mut F,
+    surface: &gfx::handle::RawTexture<R>
+) -> Result<gfx::handle::RenderTargetView<R, Srgba8>, Error>
+    where R: gfx::Resources,
+          F: gfx::Factory<R>
+{
+    let target = try!(factory.view_texture_as_render_target_raw(
+        surface, gfx::tex::RenderDesc {
+            channel: PixelFormat::Srgba8.channel_type(),
+            level: 0,
+            layer: None,
+        }));
+    Ok(Typed::new(target))
+}
+
+fn create_depth<R, F>(
+    factory: &mut F,
+    kind: gfx::tex::Kind
+) -> Result<gfx::handle::DepthStencilView<R, DepthStencil>, Error>
+    where R: gfx::Resources,
+          F: gfx::Factory<R>
+{
+    let desc = gfx::tex::Descriptor {
+        kind: kind,
+        levels: 1,
+        format: SurfaceType::D24_S8,
+        bind: gfx::DEPTH_STENCIL,
+        usage: Usage::GpuOnly.gfx_usage(),
+    };
+    let surface = try!(factory.create_texture_raw(
+        desc, Some(ChannelType::Unorm), None));
+    let view = try!(factory.view_texture_as_depth_stencil_raw(
+        &surface, gfx::tex::DepthStencilDesc {
+            level: 0,
+            layer: None,
+            flags: gfx::tex::DepthStencilFlags::empty(),
+        }));
+    Ok(Typed::new(view))
+}
@@ -0,0 +1,82 @@
+//! Textures updated with a new frame every tick.
+
+use gfx;
+use texture::{ImageSize, TextureSettings};
+
+use {Error, PixelFormat, Texture};
+
+/// Rotates between several dynamic textures of the same size,
+/// so a frame is never uploaded to a texture the GPU may still be reading.
+///
+/// Each upload goes to the least recently used texture,
+/// which then becomes the current texture for drawing.
+pub struct StreamingTexture<R> where R: gfx::Resources {
+    textures: Vec<Texture<R>>,
+    current: usize,
+}
+
+impl<R: gfx::Resources> StreamingTexture<R> {
+    /// Creates a streaming texture with the given number of textures.
+    ///
+    /// At least two textures are needed to rotate between,
+    /// and two or three are usually enough to avoid stalls.
+    /// The textures have a single level, since frames would
+    /// otherwise leave the lower mipmap levels stale.
+    pub fn new<F>(
+        factory: &mut F,
+        format: PixelFormat,
+        size: [u32; 2],
+        count: usize,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        if count < 2 {
+            return Err(Error::StreamingCount(count));
+        }
+        let settings = settings.generate_mipmap(false);
+        let mut textures = Vec::with_capacity(count);
+        for _ in 0..count {
+            textures.push(try!(Texture::new_dynamic(factory, format, size,
+                                                    &settings)));
+        }
+        Ok(StreamingTexture {
+            textures: textures,
+            current: 0,
+        })
+    }
+
+    /// Uploads a full frame stored in the pixel format of the textures
+    /// and makes it current.
+    pub fn update<C>(
+        &mut self,
+        encoder: &mut gfx::Encoder<R, C>,
+        memory: &[u8]
+    ) -> Result<(), Error>
+        where C: gfx::CommandBuffer<R>
+    {
+        let next = (self.current + 1) % self.textures.len();
+        let (width, height) = self.textures[next].get_size();
+        try!(self.textures[next].update_region(encoder, memory, [0, 0],
+                                               [width, height]));
+        self.current = next;
+        Ok(())
+    }
+
+    /// Returns the texture holding the latest frame.
+    pub fn current(&self) -> &Texture<R> {
+        &self.textures[self.current]
+    }
+
+    /// Returns the number of textures rotated between.
+    pub fn count(&self) -> usize {
+        self.textures.len()
+    }
+}
+
+impl<R> ImageSize for StreamingTexture<R> where R: gfx::Resources {
+    #[inline(always)]
+    fn get_size(&self) -> (u32, u32) {
+        self.current().get_size()
+    }
+}
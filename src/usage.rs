@@ -0,0 +1,31 @@
+//! How textures are updated after creation.
+
+use gfx;
+
+/// How a texture is updated after creation.
+///
+/// Only `Dynamic` textures can be updated, and since updates only reach
+/// one level and layer, `Dynamic` textures never have mipmaps.
+/// Textures created from a single image or from memory are `Dynamic`
+/// unless another usage is given, or `Immutable` when the settings
+/// generate mipmaps. Layered and render textures use a fixed usage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Usage {
+    /// Contents are uploaded once at creation.
+    Immutable,
+    /// Contents are updated from the CPU, for example every frame.
+    Dynamic,
+    /// Contents are written by the GPU only, for example as a render target.
+    GpuOnly,
+}
+
+impl Usage {
+    /// Returns the Gfx usage.
+    pub fn gfx_usage(&self) -> gfx::Usage {
+        match *self {
+            Usage::Immutable => gfx::Usage::Const,
+            Usage::Dynamic => gfx::Usage::Dynamic,
+            Usage::GpuOnly => gfx::Usage::GpuOnly,
+        }
+    }
+}
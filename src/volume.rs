@@ -0,0 +1,129 @@
+//! 3D volume textures.
+
+use gfx;
+use image::RgbaImage;
+use texture::TextureSettings;
+
+use {check_buffer, check_region, dimension, texture_size};
+use {Alpha, Error, PixelFormat, Texture, Usage};
+
+impl<R: gfx::Resources> Texture<R> {
+    /// Creates a 3D texture with one depth slice per image.
+    ///
+    /// All images must have the same size.
+    /// The data is stored in linear color space.
+    pub fn from_volume_slices<F>(
+        factory: &mut F,
+        slices: &[RgbaImage],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = match slices.first() {
+            Some(img) => img.dimensions(),
+            None => return Err(Error::ZeroSize),
+        };
+        let mut memory = Vec::with_capacity(
+            width as usize * height as usize * 4 * slices.len());
+        for img in slices {
+            let (w, h) = img.dimensions();
+            if [w, h] != [width, height] {
+                return Err(Error::SizeMismatch {
+                    expected: [width, height],
+                    found: [w, h],
+                });
+            }
+            memory.extend_from_slice(img);
+        }
+        Texture::create_volume(factory, &memory,
+                               [width, height, slices.len() as u32], settings)
+    }
+
+    /// Creates a 3D texture from square slices laid out left to right,
+    /// such as a 1024x32 strip holding a 32x32x32 color lookup table.
+    ///
+    /// The data is stored in linear color space.
+    pub fn from_volume_strip<F>(
+        factory: &mut F,
+        img: &RgbaImage,
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (w, h) = img.dimensions();
+        if h == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let depth = w / h;
+        if w != depth * h {
+            return Err(Error::SizeMismatch {
+                expected: [depth * h, h],
+                found: [w, h],
+            });
+        }
+
+        // Reorder so each slice is contiguous.
+        let pixels: &[u8] = img;
+        let (size, row) = (h as usize, w as usize * 4);
+        let mut memory = Vec::with_capacity(pixels.len());
+        for z in 0..depth as usize {
+            for y in 0..size {
+                let start = y * row + z * size * 4;
+                memory.extend_from_slice(&pixels[start..start + size * 4]);
+            }
+        }
+        Texture::create_volume(factory, &memory, [h, h, depth], settings)
+    }
+
+    /// Updates a box of a 3D texture with memory stored in the
+    /// pixel format of the texture.
+    ///
+    /// Returns `Error::OutOfBounds` when the box does not lie inside
+    /// the texture.
+    pub fn update_volume_region<C>(
+        &mut self,
+        encoder: &mut gfx::Encoder<R, C>,
+        memory: &[u8],
+        offset: [u32; 3],
+        size: [u32; 3]
+    ) -> Result<(), Error>
+        where C: gfx::CommandBuffer<R>
+    {
+        let (width, height) = try!(texture_size([size[0], size[1]]));
+        let depth = try!(dimension(size[2]));
+        if depth == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let (w, h, d, _) = self.surface.get_info().kind.get_dimensions();
+        try!(check_region(offset, size, [w as u32, h as u32, d as u32]));
+        try!(check_buffer(self.format, memory,
+                          [size[0], size[1] * size[2]]));
+        let mut img = self.surface.get_info().to_image_info(0);
+        img.xoffset = try!(dimension(offset[0]));
+        img.yoffset = try!(dimension(offset[1]));
+        img.zoffset = try!(dimension(offset[2]));
+        img.width = width;
+        img.height = height;
+        img.depth = depth;
+        self.update_image(encoder, img, memory)
+    }
+
+    fn create_volume<F>(
+        factory: &mut F,
+        memory: &[u8],
+        size: [u32; 3],
+        settings: &TextureSettings
+    ) -> Result<Self, Error>
+        where F: gfx::Factory<R>
+    {
+        let (width, height) = try!(texture_size([size[0], size[1]]));
+        let depth = try!(dimension(size[2]));
+        if depth == 0 {
+            return Err(Error::ZeroSize);
+        }
+        let kind = gfx::tex::Kind::D3(width, height, depth);
+        Texture::create_surface(factory, kind, PixelFormat::Rgba8,
+                                &[memory], 1, Alpha::Straight, Usage::Dynamic,
+                                settings)
+    }
+}